@@ -0,0 +1,252 @@
+//! Find `k` entries of an expense report that sum to a target.
+//!
+//! Solutions are reported by the indices of the entries in the original report, in ascending
+//! order, together with their values, so the same entry is never used twice.
+use std::collections::HashMap;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KSum {
+    pub indices: Vec<usize>,
+    pub values: Vec<i64>,
+}
+
+impl KSum {
+    fn new(nums: &[i64], mut indices: Vec<usize>) -> KSum {
+        indices.sort_unstable();
+        let values = indices.iter().map(|i| nums[*i]).collect();
+        KSum { indices, values }
+    }
+
+    pub fn product(&self) -> i64 {
+        self.values.iter().product()
+    }
+
+    /// The values, sorted, so that solutions using equal entries compare equal.
+    pub fn multiset(&self) -> Vec<i64> {
+        let mut values = self.values.clone();
+        values.sort_unstable();
+        values
+    }
+}
+
+/// Find `k` distinct entries of `nums` that sum to `target`.
+///
+/// Uses a hash set for k = 2, sort plus two pointers for k = 3 and meet-in-the-middle for larger
+/// k.
+pub fn k_sum(nums: &[i64], k: usize, target: i64) -> Option<KSum> {
+    match k {
+        0 if target == 0 => Some(KSum::new(nums, vec![])),
+        0 => None,
+        1 => nums
+            .iter()
+            .position(|n| *n == target)
+            .map(|i| KSum::new(nums, vec![i])),
+        2 => two_sum(nums, target),
+        3 => three_sum(nums, target),
+        _ => meet_in_the_middle(nums, k, target),
+    }
+}
+
+fn two_sum(nums: &[i64], target: i64) -> Option<KSum> {
+    let mut seen: HashMap<i64, usize> = HashMap::new();
+
+    for (j, num) in nums.iter().enumerate() {
+        if let Some(i) = seen.get(&(target - num)) {
+            return Some(KSum::new(nums, vec![*i, j]));
+        }
+        seen.entry(*num).or_insert(j);
+    }
+
+    None
+}
+
+/// Sort the entries, then for each one look for the other two by moving a pointer in from either
+/// end of the rest.
+pub fn three_sum(nums: &[i64], target: i64) -> Option<KSum> {
+    let mut sorted: Vec<(i64, usize)> = nums.iter().copied().zip(0..).collect();
+    sorted.sort_unstable();
+
+    for i in 0..sorted.len() {
+        if i + 2 >= sorted.len() {
+            break;
+        }
+        let (mut lo, mut hi) = (i + 1, sorted.len() - 1);
+        while lo < hi {
+            let sum = sorted[i].0 + sorted[lo].0 + sorted[hi].0;
+            if sum == target {
+                return Some(KSum::new(
+                    nums,
+                    vec![sorted[i].1, sorted[lo].1, sorted[hi].1],
+                ));
+            } else if sum < target {
+                lo += 1;
+            } else {
+                hi -= 1;
+            }
+        }
+    }
+
+    None
+}
+
+/// Every way of choosing `size` indices from a range, each in ascending order, generated one at
+/// a time in lexicographic order.
+struct Combinations {
+    end: usize,
+    current: Vec<usize>,
+    done: bool,
+}
+
+fn combinations(range: Range<usize>, size: usize) -> Combinations {
+    Combinations {
+        end: range.end,
+        current: (range.start..range.start + size).collect(),
+        done: range.start + size > range.end,
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        let result = self.current.clone();
+
+        // Advance the last index that can still move right, and put the ones after it just past it
+        let size = self.current.len();
+        match (0..size)
+            .rev()
+            .find(|&i| self.current[i] < self.end - (size - i))
+        {
+            Some(i) => {
+                self.current[i] += 1;
+                for j in i + 1..size {
+                    self.current[j] = self.current[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+
+        Some(result)
+    }
+}
+
+/// Index the sums of every combination of k / 2 entries, then for each combination of the other
+/// k - k / 2 entries look up the rest of the target. A match is only used if all of its indices
+/// come before the scanned combination's, so no entry is used twice and each solution is found
+/// once.
+///
+/// Takes O(n^(k - k / 2)) time and holds C(n, k / 2) sums, rather than trying all C(n, k)
+/// combinations.
+pub fn meet_in_the_middle(nums: &[i64], k: usize, target: i64) -> Option<KSum> {
+    search(nums, k, target, true).pop()
+}
+
+/// Every distinct combination of `k` entries of `nums` that sums to `target`, ordered by their
+/// indices.
+///
+/// Equal entries at different indices are different combinations, so a report with three 1010s
+/// has three pairs summing to 2020.
+pub fn k_sum_all(nums: &[i64], k: usize, target: i64) -> Vec<KSum> {
+    let mut solutions = search(nums, k, target, false);
+    solutions.sort_unstable_by(|a, b| a.indices.cmp(&b.indices));
+    solutions
+}
+
+fn search(nums: &[i64], k: usize, target: i64, first_only: bool) -> Vec<KSum> {
+    let sum = |indices: &[usize]| indices.iter().map(|i| nums[*i]).sum::<i64>();
+
+    let mut sums: HashMap<i64, Vec<Vec<usize>>> = HashMap::new();
+    for combination in combinations(0..nums.len(), k / 2) {
+        sums.entry(sum(&combination)).or_default().push(combination);
+    }
+
+    let mut solutions = vec![];
+    for combination in combinations(0..nums.len(), k - k / 2) {
+        let matches = match sums.get(&(target - sum(&combination))) {
+            Some(matches) => matches,
+            None => continue,
+        };
+        let before = |other: &&Vec<usize>| match (other.last(), combination.first()) {
+            (Some(last), Some(first)) => last < first,
+            _ => true,
+        };
+        for other in matches.iter().filter(before) {
+            let mut indices = other.clone();
+            indices.extend(&combination);
+            solutions.push(KSum::new(nums, indices));
+            if first_only {
+                return solutions;
+            }
+        }
+    }
+
+    solutions
+}
+
+/// The combination whose sum is nearest to a target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Closest {
+    pub solution: KSum,
+    /// The sum minus the target, so negative when the sum falls short.
+    pub difference: i64,
+}
+
+/// Find the `k` entries whose sum is nearest to `target`, for k = 2 or 3, by sorting and moving
+/// two pointers towards each other. Ties go to the smaller sum.
+///
+/// Returns None for any other k, or if there are fewer than k entries.
+pub fn closest_sum(nums: &[i64], k: usize, target: i64) -> Option<Closest> {
+    let mut sorted: Vec<(i64, usize)> = nums.iter().copied().zip(0..).collect();
+    sorted.sort_unstable();
+
+    let mut best = None;
+    match k {
+        2 => closest_pair(&sorted, 0, target, &[], &mut best),
+        3 => {
+            for (i, (value, index)) in sorted.iter().enumerate() {
+                closest_pair(&sorted, i + 1, target - value, &[*index], &mut best);
+            }
+        }
+        _ => return None,
+    }
+
+    best.map(|(difference, indices)| Closest {
+        solution: KSum::new(nums, indices),
+        difference,
+    })
+}
+
+// Look for the pair in sorted[start..] nearest to `target`, updating `best` with its difference
+// and indices (plus the `fixed` ones) if it is closer.
+fn closest_pair(
+    sorted: &[(i64, usize)],
+    start: usize,
+    target: i64,
+    fixed: &[usize],
+    best: &mut Option<(i64, Vec<usize>)>,
+) {
+    let (mut lo, mut hi) = (start, sorted.len().saturating_sub(1));
+    while lo < hi {
+        let difference = sorted[lo].0 + sorted[hi].0 - target;
+        let closer = best.as_ref().is_none_or(|(d, _)| {
+            difference.abs() < d.abs() || (difference.abs() == d.abs() && difference < *d)
+        });
+        if closer {
+            let mut indices = vec![sorted[lo].1, sorted[hi].1];
+            indices.extend(fixed);
+            *best = Some((difference, indices));
+        }
+
+        if difference == 0 {
+            return;
+        } else if difference < 0 {
+            lo += 1;
+        } else {
+            hi -= 1;
+        }
+    }
+}
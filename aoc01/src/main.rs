@@ -1,57 +1,339 @@
 use std::collections::HashSet;
 use std::io::{self, Read};
 
-use anyhow::Result;
+mod ksum;
 
-// This is just TwoSum
-fn part1(nums: &[i64], target: i64) -> Result<HashSet<i64>> {
-    let mut dual: HashSet<i64> = HashSet::new();
-    let mut result: HashSet<i64> = HashSet::new();
+use anyhow::{anyhow, Result};
+use crosscheck::{file_cases, generated_cases, Case, Command, Rng, Strategy, VerifyOpt};
+use structopt::StructOpt;
 
-    for num in nums {
-        if dual.contains(num) {
-            result.insert(*num);
-            result.insert(target - num);
-            break;
-        } else {
-            dual.insert(target - num);
+use ksum::{closest_sum, k_sum, k_sum_all, KSum};
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    /// Find this many entries that sum to the target, instead of solving both parts
+    #[structopt(short, long)]
+    k: Option<usize>,
+
+    /// The sum to look for
+    #[structopt(short, long, default_value = "2020")]
+    target: i64,
+
+    /// List every combination of entries that sums to the target, not just the first
+    #[structopt(short, long)]
+    all: bool,
+
+    /// Find the 2 or 3 entries whose sum is nearest to the target, if none match exactly
+    #[structopt(short, long, conflicts_with = "all")]
+    closest: bool,
+
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+const EXAMPLE: [i64; 6] = [1721, 979, 366, 299, 675, 1456];
+
+// This is 3Sum, reusing the work of 2Sum: each entry looks for a pair among the entries before it
+fn part2_two_sum(nums: &[i64], target: i64) -> Option<KSum> {
+    (2..nums.len()).find_map(|i| {
+        k_sum(&nums[..i], 2, target - nums[i]).map(|pair| {
+            let mut indices = pair.indices;
+            indices.push(i);
+            KSum {
+                values: indices.iter().map(|j| nums[*j]).collect(),
+                indices,
+            }
+        })
+    })
+}
+
+// Try every triple of entries
+fn part2_naive(nums: &[i64], target: i64) -> Option<KSum> {
+    for i in 0..nums.len() {
+        for j in i + 1..nums.len() {
+            for k in j + 1..nums.len() {
+                if nums[i] + nums[j] + nums[k] == target {
+                    return Some(KSum {
+                        indices: vec![i, j, k],
+                        values: vec![nums[i], nums[j], nums[k]],
+                    });
+                }
+            }
         }
     }
 
-    Ok(result)
+    None
 }
 
-// This is 3Sum. In this approach, I'm trying to reuse the work of 2 sum to make
-// the code simpler. Probably isn't the best way.
-fn part2(nums: &mut Vec<i64>, target: i64) -> Result<HashSet<i64>> {
-    let mut result: HashSet<i64> = HashSet::new();
-    nums.sort();
+fn solve(nums: &[i64], k: usize, target: i64) -> Result<KSum> {
+    k_sum(nums, k, target).ok_or_else(|| anyhow!("No {} entries sum to {}.", k, target))
+}
+
+fn print_all(nums: &[i64], k: usize, target: i64) {
+    let solutions = k_sum_all(nums, k, target);
+    let multisets: HashSet<Vec<i64>> = solutions.iter().map(KSum::multiset).collect();
+    println!(
+        "{} entries summing to {}: {} combinations, {} distinct multisets",
+        k,
+        target,
+        solutions.len(),
+        multisets.len()
+    );
+    for solution in &solutions {
+        println!(
+            "    indices {:?}, values {:?}, product {}",
+            solution.indices,
+            solution.multiset(),
+            solution.product()
+        );
+    }
+}
+
+fn print_closest(nums: &[i64], k: usize, target: i64) -> Result<()> {
+    if k != 2 && k != 3 {
+        return Err(anyhow!(
+            "Closest sums are only supported for 2 or 3 entries."
+        ));
+    }
+    let closest =
+        closest_sum(nums, k, target).ok_or_else(|| anyhow!("Fewer than {} entries.", k))?;
+    println!(
+        "{} entries closest to {}: indices {:?}, values {:?}, difference {:+}, product {}",
+        k,
+        target,
+        closest.solution.indices,
+        closest.solution.values,
+        closest.difference,
+        closest.solution.product()
+    );
 
-    for i in 2..nums.len() {
-        let sub_target: i64 = target - nums[i];
-        let two_sum = part1(&nums[..i], sub_target)?;
-        if two_sum.len() == 2 {
-            two_sum.iter().for_each(|n| {
-                let _ = result.insert(*n);
-            });
-            result.insert(nums[i]);
-            break;
+    Ok(())
+}
+
+fn found(solution: Option<KSum>) -> Result<KSum> {
+    solution.ok_or_else(|| anyhow!("No solution found."))
+}
+
+fn part2_strategies() -> Vec<Strategy<Vec<i64>, KSum>> {
+    vec![
+        Strategy::new("sort and two pointers", |nums| {
+            found(ksum::three_sum(nums, 2020))
+        }),
+        Strategy::new("meet in the middle", |nums| {
+            found(ksum::meet_in_the_middle(nums, 3, 2020))
+        }),
+        Strategy::new("3Sum reusing 2Sum", |nums| found(part2_two_sum(nums, 2020))),
+        Strategy::new("naive", |nums| found(part2_naive(nums, 2020))),
+    ]
+}
+
+fn parse_input(input: &str) -> Result<Vec<i64>> {
+    Ok(input
+        .lines()
+        .map(|n| n.parse::<i64>())
+        .collect::<Result<Vec<i64>, _>>()?)
+}
+
+// Distinct entries with exactly one triple that sums to 2020
+fn generate(rng: &mut Rng) -> Vec<i64> {
+    loop {
+        let len = rng.range(3, 30) as usize;
+        let mut nums: HashSet<i64> = HashSet::new();
+        while nums.len() < len {
+            nums.insert(rng.range(1, 2020));
+        }
+        let mut nums: Vec<i64> = nums.into_iter().collect();
+        nums.sort_unstable();
+        rng.shuffle(&mut nums);
+
+        let mut triples = 0;
+        for i in 0..nums.len() {
+            for j in i + 1..nums.len() {
+                for k in j + 1..nums.len() {
+                    if nums[i] + nums[j] + nums[k] == 2020 {
+                        triples += 1;
+                    }
+                }
+            }
+        }
+        if triples == 1 {
+            return nums;
         }
     }
+}
 
-    Ok(result)
+fn verify(opt: &VerifyOpt) -> Result<()> {
+    let mut cases = vec![Case::new("example", EXAMPLE.to_vec())];
+    cases.extend(file_cases(opt, parse_input)?);
+    cases.extend(generated_cases(opt, generate));
+
+    crosscheck::run("Part 2", &part2_strategies(), &cases)
 }
 
 fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    if let Some(Command::Verify(opt)) = opt.cmd {
+        return verify(&opt);
+    }
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
-    let mut nums: Vec<i64> = input.lines().map(|n| n.parse::<i64>().unwrap()).collect();
+    let nums = parse_input(&input)?;
 
-    println!("Part 1: {}", part1(&nums, 2020)?.iter().product::<i64>());
-    println!(
-        "Part 2: {}",
-        part2(&mut nums, 2020)?.iter().product::<i64>()
-    );
+    let ks = match opt.k {
+        Some(k) => vec![k],
+        None => vec![2, 3],
+    };
+
+    if opt.all {
+        for k in ks {
+            print_all(&nums, k, opt.target);
+        }
+        return Ok(());
+    }
+
+    if opt.closest {
+        for k in ks {
+            print_closest(&nums, k, opt.target)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(k) = opt.k {
+        let solution = solve(&nums, k, opt.target)?;
+        println!("Indices: {:?}", solution.indices);
+        println!("Values: {:?}", solution.values);
+        println!("Product: {}", solution.product());
+        return Ok(());
+    }
+
+    // Report a missing solution rather than the product of nothing
+    match solve(&nums, 2, opt.target) {
+        Ok(v) => println!("Part 1: {}", v.product()),
+        Err(e) => eprintln!("Part 1: {}", e),
+    }
+    match solve(&nums, 3, opt.target) {
+        Ok(v) => println!("Part 2: {}", v.product()),
+        Err(e) => eprintln!("Part 2: {}", e),
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_k_sum() -> Result<()> {
+        let pair = solve(&EXAMPLE, 2, 2020)?;
+        assert_eq!(vec![0, 3], pair.indices);
+        assert_eq!(514579, pair.product());
+
+        // 979 + 366 + 299 + 675
+        let four = solve(&EXAMPLE, 4, 2319)?;
+        assert_eq!(vec![1, 2, 3, 4], four.indices);
+        assert_eq!(vec![979, 366, 299, 675], four.values);
+
+        assert!(k_sum(&EXAMPLE, 7, 2020).is_none());
+        assert!(k_sum(&EXAMPLE, 5, 1).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_closest_sum() -> Result<()> {
+        // Exact matches have no difference
+        let pair = closest_sum(&EXAMPLE, 2, 2020).unwrap();
+        assert_eq!(0, pair.difference);
+        assert_eq!(vec![0, 3], pair.solution.indices);
+
+        // 1721 + 366 = 2087 and 675 + 1456 = 2131 are both 22 away; the smaller sum wins
+        let pair = closest_sum(&EXAMPLE, 2, 2109).unwrap();
+        assert_eq!(-22, pair.difference);
+        assert_eq!(vec![1721, 366], pair.solution.values);
+
+        // 299 + 366 + 675 = 1340
+        let triple = closest_sum(&EXAMPLE, 3, 1339).unwrap();
+        assert_eq!(1, triple.difference);
+        assert_eq!(vec![2, 3, 4], triple.solution.indices);
+
+        assert!(closest_sum(&EXAMPLE, 4, 2020).is_none());
+        assert!(closest_sum(&EXAMPLE[..2], 3, 2020).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_k_sum_all_duplicates() -> Result<()> {
+        let nums = [1010, 5, 1010, 2015, 1010];
+
+        let pairs = k_sum_all(&nums, 2, 2020);
+        let indices: Vec<Vec<usize>> = pairs.iter().map(|p| p.indices.clone()).collect();
+        assert_eq!(
+            vec![vec![0, 2], vec![0, 4], vec![1, 3], vec![2, 4]],
+            indices
+        );
+        assert_eq!(1020100, pairs[0].product());
+        assert_eq!(vec![1010, 1010], solve(&nums, 2, 2020)?.values);
+
+        // Three ways of picking 5, 2015 and a 1010, one of picking all three 1010s
+        let triples = k_sum_all(&nums, 3, 3030);
+        let multisets: HashSet<Vec<i64>> = triples.iter().map(KSum::multiset).collect();
+        assert_eq!(4, triples.len());
+        assert_eq!(2, multisets.len());
+        assert_eq!(vec![1010, 1010, 1010], triples[1].multiset());
+        assert_eq!(3030, solve(&nums, 3, 3030)?.values.iter().sum::<i64>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_k_sum_all_halves() -> Result<()> {
+        // Every subset of the example, by its indices in ascending order
+        for k in 0..=EXAMPLE.len() {
+            let subsets: Vec<Vec<usize>> = (0..1 << EXAMPLE.len())
+                .map(|mask: usize| (0..EXAMPLE.len()).filter(|i| mask >> i & 1 == 1).collect())
+                .filter(|subset: &Vec<usize>| subset.len() == k)
+                .collect();
+
+            for subset in subsets {
+                let target = subset.iter().map(|i| EXAMPLE[*i]).sum();
+                let solutions = k_sum_all(&EXAMPLE, k, target);
+                assert!(
+                    solutions.iter().any(|s| s.indices == subset),
+                    "{:?} sums to {}",
+                    subset,
+                    target
+                );
+                assert!(solutions
+                    .iter()
+                    .all(|s| s.values.iter().sum::<i64>() == target));
+                assert!(k_sum(&EXAMPLE, k, target).is_some());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_k_sum_many_entries() {
+        // Trying every combination of 4 of 1000 entries would take hours, but only pairs are
+        // indexed and scanned. Every sum is even, so there is no solution.
+        let nums: Vec<i64> = (0..1000).map(|i| i * 2).collect();
+        assert!(k_sum(&nums, 4, 2021).is_none());
+        assert!(k_sum_all(&nums, 4, 2021).is_empty());
+        assert_eq!(vec![0, 1, 2, 999], k_sum(&nums, 4, 2004).unwrap().indices);
+    }
+
+    #[test]
+    fn test_part2_strategies() -> Result<()> {
+        for strategy in part2_strategies() {
+            let triple = (strategy.run)(&EXAMPLE.to_vec())?;
+            assert_eq!(vec![1, 2, 4], triple.indices, "{}", strategy.name);
+            assert_eq!(241861950, triple.product(), "{}", strategy.name);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! A per-line audit of a password database: whether each line is valid under each policy, why
+//! not, and which lines could not be parsed at all.
+use std::io::Write;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+use serde::Serialize;
+
+use crate::policy::PasswordPolicy;
+use crate::Password;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Format> {
+        match s {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(anyhow!("Unknown report format: \"{}\"", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Valid,
+    Invalid,
+    Unparsable,
+}
+
+impl Status {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Status::Valid => "valid",
+            Status::Invalid => "invalid",
+            Status::Unparsable => "unparsable",
+        }
+    }
+}
+
+/// The result of checking one line against one policy. Lines that can't be parsed at all get a
+/// single row with no policy, while rules that only one policy rejects (e.g. position 0) are
+/// unparsable under that policy.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Row {
+    /// 1-indexed line number in the input
+    pub line: usize,
+    pub input: String,
+    pub policy: Option<String>,
+    pub status: Status,
+    pub reason: Option<String>,
+}
+
+pub fn audit(input: &str, policies: &[Box<dyn PasswordPolicy>]) -> Vec<Row> {
+    let mut rows = vec![];
+
+    for (i, line) in input.lines().enumerate() {
+        let row = |policy, status, reason| Row {
+            line: i + 1,
+            input: line.to_string(),
+            policy,
+            status,
+            reason,
+        };
+
+        match line.parse::<Password>() {
+            Ok(pass) => {
+                for policy in policies {
+                    let name = Some(policy.name());
+                    rows.push(match (policy.parse_rule(&pass), policy.check(&pass)) {
+                        (Err(e), _) => row(name, Status::Unparsable, Some(e.to_string())),
+                        (_, Ok(())) => row(name, Status::Valid, None),
+                        (_, Err(reason)) => row(name, Status::Invalid, Some(reason)),
+                    });
+                }
+            }
+            Err(e) => rows.push(row(None, Status::Unparsable, Some(e.to_string()))),
+        }
+    }
+
+    rows
+}
+
+pub fn write<W: Write>(out: &mut W, rows: &[Row], format: Format) -> Result<()> {
+    match format {
+        Format::Csv => {
+            let mut csv = csv::Writer::from_writer(&mut *out);
+            csv.write_record(["line", "input", "policy", "status", "reason"])?;
+            for row in rows {
+                csv.write_record([
+                    &row.line.to_string(),
+                    &row.input,
+                    row.policy.as_deref().unwrap_or(""),
+                    row.status.as_str(),
+                    row.reason.as_deref().unwrap_or(""),
+                ])?;
+            }
+            csv.flush()?;
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, rows)?;
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
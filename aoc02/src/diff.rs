@@ -0,0 +1,52 @@
+//! Which passwords change validity when switching from one policy to another.
+use crate::policy::PasswordPolicy;
+use crate::Password;
+
+/// A line of the input, with its 1-indexed line number.
+pub type Line = (usize, String);
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Diff {
+    /// Valid under the old policy, but not the new one
+    pub lost: Vec<Line>,
+    /// Valid under the new policy, but not the old one
+    pub gained: Vec<Line>,
+    /// Lines that could not be parsed, or whose rule either policy can't interpret, and so are in
+    /// neither list
+    pub unparsable: usize,
+}
+
+pub fn diff(input: &str, old: &dyn PasswordPolicy, new: &dyn PasswordPolicy) -> Diff {
+    let mut diff = Diff::default();
+
+    for (i, line) in input.lines().enumerate() {
+        let pass = match line.parse::<Password>() {
+            Ok(pass) if old.parse_rule(&pass).is_ok() && new.parse_rule(&pass).is_ok() => pass,
+            _ => {
+                diff.unparsable += 1;
+                continue;
+            }
+        };
+
+        match (old.is_valid(&pass), new.is_valid(&pass)) {
+            (true, false) => diff.lost.push((i + 1, line.to_string())),
+            (false, true) => diff.gained.push((i + 1, line.to_string())),
+            _ => (),
+        }
+    }
+
+    diff
+}
+
+pub fn print(diff: &Diff, old: &str, new: &str) {
+    let sections = [(old, new, &diff.lost), (new, old, &diff.gained)];
+    for (valid, invalid, lines) in sections.iter() {
+        println!("Valid under {} but not {}: {}", valid, invalid, lines.len());
+        for (line, input) in lines.iter() {
+            println!("    line {}: {}", line, input);
+        }
+    }
+    if diff.unparsable > 0 {
+        println!("Unparsable lines: {}", diff.unparsable);
+    }
+}
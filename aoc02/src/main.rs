@@ -5,6 +5,44 @@ use std::str::FromStr;
 use anyhow::{anyhow, Error, Result};
 use lazy_static::lazy_static;
 use regex::Regex;
+use structopt::StructOpt;
+
+mod audit;
+mod diff;
+mod policy;
+
+use policy::{Frequency, PasswordPolicy, Position};
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    /// Count the passwords that are valid under this policy instead of solving both parts. Can
+    /// be repeated.
+    #[structopt(short, long = "policy", number_of_values = 1)]
+    policies: Vec<String>,
+
+    /// List the built-in policies
+    #[structopt(long)]
+    list_policies: bool,
+
+    /// Print a report of every line under each policy (frequency and position by default), as
+    /// "csv" or "json"
+    #[structopt(long)]
+    audit: Option<audit::Format>,
+
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// List the passwords whose validity changes when switching from one policy to another
+    Diff {
+        #[structopt(default_value = "frequency")]
+        old: String,
+        #[structopt(default_value = "position")]
+        new: String,
+    },
+}
 
 #[derive(Debug)]
 struct Password {
@@ -22,9 +60,9 @@ impl FromStr for Password {
         lazy_static! {
             static ref RE: Regex = Regex::new(
                 r"(?x)
-                (?P<num_1>[0-9]+)-(?P<num_2>[0-9]+)\s+
-                (?P<target>\w):\s+
-                (?P<password>\w+)
+                ^(?P<num_1>[0-9]+)-(?P<num_2>[0-9]+)\s+
+                (?P<target>\S):\s+
+                (?P<password>\S+)\s*$
                 "
             )
             .unwrap();
@@ -35,17 +73,11 @@ impl FromStr for Password {
             Some(c) => c,
         };
 
-        // Count letter frequencies
+        // Count the frequency of each character (Unicode scalar value)
         let password: String = caps["password"].parse()?;
         let mut counter: HashMap<char, usize> = HashMap::new();
         for c in password.chars() {
-            if counter.contains_key(&c) {
-                if let Some(count) = counter.get_mut(&c) {
-                    *count += 1;
-                }
-            } else {
-                counter.insert(c, 1);
-            }
+            *counter.entry(c).or_insert(0) += 1;
         }
 
         Ok(Password {
@@ -58,67 +90,188 @@ impl FromStr for Password {
     }
 }
 
-// How many passwords are valid?
-fn part1(input: &str) -> Result<()> {
-    let passwords: Vec<Password> = input.lines().filter_map(|l| l.parse().ok()).collect();
+// Lines that can't be parsed are skipped, with a warning
+fn parse_passwords(input: &str) -> Vec<Password> {
+    let mut passwords = vec![];
+    for (i, line) in input.lines().enumerate() {
+        match line.parse() {
+            Ok(pass) => passwords.push(pass),
+            Err(e) => eprintln!("Skipping line {}: {}", i + 1, e),
+        }
+    }
+    passwords
+}
 
-    let valid_passwords: Vec<&Password> = passwords
+// How many passwords are valid under the policy? Passwords whose rule the policy can't interpret
+// are skipped, with a warning
+fn count_valid(passwords: &[Password], policy: &dyn PasswordPolicy) -> usize {
+    passwords
         .iter()
-        .filter_map(|pass| match pass.counter.get(&pass.target) {
-            Some(freq) => {
-                if freq < &pass.num_1 || freq > &pass.num_2 {
-                    None
-                } else {
-                    Some(pass)
-                }
+        .filter(|p| match policy.parse_rule(p) {
+            Ok(()) => policy.is_valid(p),
+            Err(e) => {
+                eprintln!("Skipping {} under {}: {}", p.password, policy.name(), e);
+                false
             }
-            None => None,
         })
-        .collect();
+        .count()
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    if opt.list_policies {
+        for (name, description) in policy::BUILTIN.iter() {
+            println!("{:<22} {}", name, description);
+        }
+        return Ok(());
+    }
+
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+
+    if let Some(Command::Diff { old, new }) = opt.cmd {
+        let (old, new) = (policy::by_name(&old)?, policy::by_name(&new)?);
+        let diff = diff::diff(&input, old.as_ref(), new.as_ref());
+        diff::print(&diff, &old.name(), &new.name());
+        return Ok(());
+    }
+
+    if let Some(format) = opt.audit {
+        let specs = if opt.policies.is_empty() {
+            vec!["frequency".to_string(), "position".to_string()]
+        } else {
+            opt.policies
+        };
+        let policies = specs
+            .iter()
+            .map(|spec| policy::by_name(spec))
+            .collect::<Result<Vec<_>>>()?;
+        let rows = audit::audit(&input, &policies);
+        return audit::write(&mut io::stdout().lock(), &rows, format);
+    }
+
+    let passwords = parse_passwords(&input);
+    if opt.policies.is_empty() {
+        println!("Part 1: {}", count_valid(&passwords, &Frequency));
+        println!("Part 2: {}", count_valid(&passwords, &Position));
+        return Ok(());
+    }
 
-    println!("Part 1: {}", valid_passwords.len());
+    for spec in &opt.policies {
+        let policy = policy::by_name(spec)?;
+        println!(
+            "{}: {}",
+            policy.name(),
+            count_valid(&passwords, policy.as_ref())
+        );
+    }
 
     Ok(())
 }
 
-// How many passwords are valid with the corrected rules?
-fn part2(input: &str) -> Result<()> {
-    let passwords: Vec<Password> = input.lines().filter_map(|l| l.parse().ok()).collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let valid_passwords: Vec<&Password> = passwords
-        .iter()
-        .filter_map(|pass| {
-            let mut count = 0;
+    #[test]
+    fn test_example() -> Result<()> {
+        let passwords = parse_passwords("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc");
 
-            // -1 because the input is 1-indexed
-            match pass.password.chars().nth(pass.num_1 - 1) {
-                Some(c) if c == pass.target => count += 1,
-                _ => {}
-            }
-            match pass.password.chars().nth(pass.num_2 - 1) {
-                Some(c) if c == pass.target => count += 1,
-                _ => {}
-            }
+        assert_eq!(2, count_valid(&passwords, &Frequency));
+        assert_eq!(1, count_valid(&passwords, &Position));
+        assert_eq!(
+            2,
+            count_valid(
+                &passwords,
+                policy::by_name("no-repeated-adjacent")?.as_ref()
+            )
+        );
+        assert_eq!(
+            2,
+            count_valid(&passwords, policy::by_name("min-distinct=5")?.as_ref())
+        );
+        assert_eq!(
+            3,
+            count_valid(&passwords, policy::by_name("regex=^[a-g]+$")?.as_ref())
+        );
+        assert!(policy::by_name("min-distinct").is_err());
+        assert!(policy::by_name("unknown").is_err());
 
-            if count == 1 {
-                Some(pass)
-            } else {
-                None
-            }
-        })
-        .collect();
+        Ok(())
+    }
 
-    println!("Part 2: {}", valid_passwords.len());
+    #[test]
+    fn test_audit() -> Result<()> {
+        let policies = vec![policy::by_name("frequency")?, policy::by_name("position")?];
+        let rows = audit::audit("1-3 a: abcde\n1-3 b: cdefg\nnot a password", &policies);
 
-    Ok(())
-}
+        assert_eq!(5, rows.len());
+        assert_eq!(audit::Status::Valid, rows[0].status);
+        assert_eq!(
+            Some("'b' occurs 0 times, allowed 1-3".to_string()),
+            rows[2].reason
+        );
+        assert_eq!(
+            Some("'b' is at neither position 1 nor 3".to_string()),
+            rows[3].reason
+        );
+        assert_eq!(audit::Status::Unparsable, rows[4].status);
+        assert_eq!(3, rows[4].line);
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+        let mut csv = vec![];
+        audit::write(&mut csv, &rows[2..3], audit::Format::Csv)?;
+        assert_eq!(
+            "line,input,policy,status,reason\n2,1-3 b: cdefg,frequency,invalid,\"'b' occurs 0 times, allowed 1-3\"\n",
+            String::from_utf8(csv)?
+        );
 
-    part1(&input)?;
-    part2(&input)?;
+        Ok(())
+    }
 
-    Ok(())
+    #[test]
+    fn test_diff() -> Result<()> {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-2 a: abaa\nbad";
+        let diff = diff::diff(input, &Frequency, &Position);
+
+        assert_eq!(vec![(3, "2-9 c: ccccccccc".to_string())], diff.lost);
+        assert_eq!(vec![(4, "1-2 a: abaa".to_string())], diff.gained);
+        assert_eq!(1, diff.unparsable);
+
+        // Position 0 is neither lost nor gained, since the position policy can't interpret it, but
+        // a reversed range is a frequency that can never be met
+        let diff = diff::diff("0-3 a: abc\n3-1 a: abc", &Frequency, &Position);
+        assert!(diff.lost.is_empty());
+        assert_eq!(vec![(2, "3-1 a: abc".to_string())], diff.gained);
+        assert_eq!(1, diff.unparsable);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_positions() -> Result<()> {
+        let check = |line: &str| -> Result<Result<(), String>> {
+            let pass: Password = line.parse()?;
+            Position.parse_rule(&pass)?;
+            Ok(Position.check(&pass))
+        };
+
+        // Position 0 is a rule error, not an underflow
+        assert!(check("0-2 a: abc").is_err());
+        assert!(check("1-0 a: abc").is_err());
+        assert!(!Frequency.is_valid(&"0-2 a: bbb".parse()?));
+
+        // Rules a policy can't interpret are skipped rather than counted
+        let passwords = parse_passwords("0-3 a: abc\n1-3 a: abc");
+        assert_eq!(2, count_valid(&passwords, &Frequency));
+        assert_eq!(1, count_valid(&passwords, &Position));
+
+        // Any non-whitespace characters, counted by Unicode scalar value
+        assert_eq!(Ok(()), check("1-2 -: -a-b")?);
+        assert!(check("2-4 !: a!b!")?.is_err());
+        assert_eq!(Ok(()), check("2-3 é: aéb")?);
+        assert_eq!(Ok(()), check("3-9 ü: ßéü")?);
+        assert!("1-3 a: ab cd".parse::<Password>().is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,190 @@
+//! Password policies, which can be selected by name from the command line.
+//!
+//! A policy is given as `name` or `name=argument`, e.g. `min-distinct=5` or `regex=^[a-z]+$`.
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::Password;
+
+pub trait PasswordPolicy {
+    /// The name of the policy, as given on the command line.
+    fn name(&self) -> String;
+
+    /// Check that the password's rule can be interpreted by this policy.
+    fn parse_rule(&self, _password: &Password) -> Result<()> {
+        Ok(())
+    }
+
+    /// Ok if the password is valid, otherwise the reason it is not.
+    fn check(&self, password: &Password) -> Result<(), String>;
+
+    fn is_valid(&self, password: &Password) -> bool {
+        self.check(password).is_ok()
+    }
+}
+
+/// Every built-in policy, with a short description.
+pub const BUILTIN: [(&str, &str); 5] = [
+    (
+        "frequency",
+        "the letter occurs between num_1 and num_2 times (part 1)",
+    ),
+    (
+        "position",
+        "the letter is at exactly one of positions num_1 and num_2 (part 2)",
+    ),
+    (
+        "no-repeated-adjacent",
+        "no character appears twice in a row",
+    ),
+    (
+        "min-distinct=N",
+        "the password has at least N distinct characters",
+    ),
+    ("regex=PATTERN", "the password matches PATTERN"),
+];
+
+/// Look up a policy by name, with its argument if it takes one.
+pub fn by_name(spec: &str) -> Result<Box<dyn PasswordPolicy>> {
+    let (name, arg) = match spec.find('=') {
+        Some(i) => (&spec[..i], Some(&spec[i + 1..])),
+        None => (spec, None),
+    };
+
+    let policy: Box<dyn PasswordPolicy> = match (name, arg) {
+        ("frequency", None) => Box::new(Frequency),
+        ("position", None) => Box::new(Position),
+        ("no-repeated-adjacent", None) => Box::new(NoRepeatedAdjacent),
+        ("min-distinct", Some(n)) => Box::new(MinDistinct(n.parse()?)),
+        ("regex", Some(pattern)) => Box::new(Matches(Regex::new(pattern)?)),
+        _ => return Err(anyhow!("Unknown policy: \"{}\"", spec)),
+    };
+
+    Ok(policy)
+}
+
+/// The letter occurs between num_1 and num_2 times, inclusive. A password without the letter is
+/// never valid, even if num_1 is 0.
+pub struct Frequency;
+
+impl PasswordPolicy for Frequency {
+    fn name(&self) -> String {
+        "frequency".to_string()
+    }
+
+    fn check(&self, pass: &Password) -> Result<(), String> {
+        match pass.counter.get(&pass.target) {
+            Some(freq) if (pass.num_1..=pass.num_2).contains(freq) => Ok(()),
+            freq => Err(format!(
+                "'{}' occurs {} times, allowed {}-{}",
+                pass.target,
+                freq.unwrap_or(&0),
+                pass.num_1,
+                pass.num_2
+            )),
+        }
+    }
+}
+
+/// The letter is at exactly one of the (1-indexed) positions num_1 and num_2.
+///
+/// Positions count Unicode scalar values, like the frequencies in [`Frequency`].
+pub struct Position;
+
+impl Position {
+    // The rule's positions, 0-indexed
+    fn positions(pass: &Password) -> Result<(usize, usize)> {
+        if pass.num_1 == 0 || pass.num_2 == 0 {
+            return Err(anyhow!(
+                "Invalid rule {}-{}: positions start at 1",
+                pass.num_1,
+                pass.num_2
+            ));
+        }
+        Ok((pass.num_1 - 1, pass.num_2 - 1))
+    }
+}
+
+impl PasswordPolicy for Position {
+    fn name(&self) -> String {
+        "position".to_string()
+    }
+
+    fn parse_rule(&self, pass: &Password) -> Result<()> {
+        Position::positions(pass).map(|_| ())
+    }
+
+    fn check(&self, pass: &Password) -> Result<(), String> {
+        let (first, second) = Position::positions(pass).map_err(|e| e.to_string())?;
+        let chars: Vec<char> = pass.password.chars().collect();
+        let at = |position: usize| chars.get(position) == Some(&pass.target);
+        match (at(first), at(second)) {
+            (true, true) => Err(format!(
+                "'{}' is at both positions {} and {}",
+                pass.target, pass.num_1, pass.num_2
+            )),
+            (false, false) => Err(format!(
+                "'{}' is at neither position {} nor {}",
+                pass.target, pass.num_1, pass.num_2
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct NoRepeatedAdjacent;
+
+impl PasswordPolicy for NoRepeatedAdjacent {
+    fn name(&self) -> String {
+        "no-repeated-adjacent".to_string()
+    }
+
+    fn check(&self, pass: &Password) -> Result<(), String> {
+        let chars: Vec<char> = pass.password.chars().collect();
+        match chars.windows(2).position(|w| w[0] == w[1]) {
+            Some(i) => Err(format!(
+                "'{}' is repeated at positions {} and {}",
+                chars[i],
+                i + 1,
+                i + 2
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+pub struct MinDistinct(pub usize);
+
+impl PasswordPolicy for MinDistinct {
+    fn name(&self) -> String {
+        format!("min-distinct={}", self.0)
+    }
+
+    fn check(&self, pass: &Password) -> Result<(), String> {
+        if pass.counter.len() >= self.0 {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} distinct characters, need at least {}",
+                pass.counter.len(),
+                self.0
+            ))
+        }
+    }
+}
+
+pub struct Matches(pub Regex);
+
+impl PasswordPolicy for Matches {
+    fn name(&self) -> String {
+        format!("regex={}", self.0)
+    }
+
+    fn check(&self, pass: &Password) -> Result<(), String> {
+        if self.0.is_match(&pass.password) {
+            Ok(())
+        } else {
+            Err(format!("does not match \"{}\"", self.0))
+        }
+    }
+}
@@ -1,22 +1,172 @@
+use std::convert::TryFrom;
+use std::fs;
 use std::io::{self, Read};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Instant;
 
 use anyhow::{anyhow, Error, Result};
+use crosscheck::Rng;
+use pnm::{FrameOpt, FrameWriter, Image};
+use structopt::StructOpt;
 
-/// Struct to represent the input as 2D vectors
+mod render;
+mod route;
+mod row;
+mod search;
+mod terrain;
+
+use render::PathView;
+use row::Row;
+use search::Bounds;
+use terrain::{Cost, Legend, Terrain};
+
+/// The slopes checked in part 2, as (right, down)
+const SLOPES: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+/// The colors of open squares, trees, and open squares and trees on the path
+const PALETTE: &str = "f0f8ff,228b22,ffa500,b22222";
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    // Frames are images of the map with each part 2 slope's path overlaid
+    #[structopt(flatten)]
+    frames: FrameOpt,
+
+    /// Also count the trees on this slope, given as "right,down" steps (e.g. "-1,2") or as a
+    /// ratio "right/down" that only visits squares the line passes exactly through (e.g. "2/3").
+    /// Can be repeated.
+    #[structopt(long = "slope", number_of_values = 1, allow_hyphen_values = true)]
+    slopes: Vec<Step>,
+
+    /// Print the map with the path of this slope overlaid, like the puzzle text
+    #[structopt(long, allow_hyphen_values = true)]
+    render: Option<Step>,
+
+    /// Read the map using the terrain kinds and costs in this legend file, and report the cost of
+    /// the part 1 slope and any extra slopes. Squares drawn as '#' are still trees.
+    #[structopt(long, parse(from_os_str))]
+    legend: Option<PathBuf>,
+
+    /// Column to start the extra slopes (and the search) from
+    #[structopt(long, default_value = "0")]
+    start: usize,
+
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Rank every slope within the bounds by the number of trees it hits
+    Search {
+        /// Smallest step to the right (negative steps go left)
+        #[structopt(long, default_value = "-10", allow_hyphen_values = true)]
+        min_right: isize,
+
+        /// Largest step to the right
+        #[structopt(long, default_value = "10", allow_hyphen_values = true)]
+        max_right: isize,
+
+        /// Largest step down
+        #[structopt(long, default_value = "5")]
+        max_down: usize,
+
+        /// How many slopes to list from each end of the ranking
+        #[structopt(long, default_value = "5")]
+        top: usize,
+    },
+    /// Find the route from the top row to the bottom that hits the fewest trees (or costs the
+    /// least, with a legend)
+    Route {
+        /// A move the route can make, as "right,down". Can be repeated. Defaults to down,
+        /// down-left and down-right.
+        #[structopt(long = "move", number_of_values = 1, allow_hyphen_values = true)]
+        moves: Vec<Step>,
+
+        /// Column to start from, instead of the cheapest one
+        #[structopt(long)]
+        from: Option<usize>,
+    },
+    /// Time counting the part 2 slopes one at a time against a single pass, on a generated map
+    Bench {
+        /// Height of the generated map
+        #[structopt(long, default_value = "1000000")]
+        rows: usize,
+
+        /// Width of the generated map
+        #[structopt(long, default_value = "31")]
+        width: usize,
+
+        /// Percentage of squares with a tree, from 0 to 100
+        #[structopt(long, default_value = "20", parse(try_from_str = parse_density))]
+        density: i64,
+
+        #[structopt(long, default_value = "2020")]
+        seed: u64,
+    },
+}
+
+/// A straight path down the map: `dx` squares right (left if negative) for every `dy` down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Step {
+    dx: isize,
+    dy: usize,
+}
+
+impl Step {
+    /// The smallest step along the line with gradient `right / down`, so that every square the
+    /// line passes exactly through is visited.
+    fn ratio(right: isize, down: usize) -> Step {
+        let mut gcd = (right.unsigned_abs(), down);
+        while gcd.1 != 0 {
+            gcd = (gcd.1, gcd.0 % gcd.1);
+        }
+        Step {
+            dx: right / gcd.0 as isize,
+            dy: down / gcd.0,
+        }
+    }
+}
+
+impl FromStr for Step {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Step> {
+        let (step, ratio) = match (s.split_once(','), s.split_once('/')) {
+            (Some(step), None) => (step, false),
+            (None, Some(step)) => (step, true),
+            _ => return Err(anyhow!("Invalid slope: \"{}\"", s)),
+        };
+        let dx: isize = step.0.trim().parse()?;
+        let dy: usize = step.1.trim().parse()?;
+        if dy == 0 {
+            return Err(anyhow!("Slope must move down: \"{}\"", s));
+        }
+
+        Ok(if ratio {
+            Step::ratio(dx, dy)
+        } else {
+            Step { dx, dy }
+        })
+    }
+}
+
+/// Struct to represent the input as rows of bits, with the kinds of terrain if the map was read
+/// with a legend
 #[derive(Debug)]
 struct Slope {
-    trees: Vec<Vec<bool>>,
+    trees: Vec<Row>,
+    terrain: Option<Terrain>,
 }
 
 impl FromStr for Slope {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Slope> {
-        let mut trees: Vec<Vec<bool>> = vec![];
+        let mut trees: Vec<Row> = vec![];
         for line in s.lines() {
-            let mut row: Vec<bool> = vec![];
+            let mut row = Row::new();
             for c in line.chars() {
                 // If there is a tree, it is true
                 match c {
@@ -27,27 +177,131 @@ impl FromStr for Slope {
             }
             trees.push(row);
         }
-        Ok(Slope { trees })
+        Ok(Slope {
+            trees,
+            terrain: None,
+        })
     }
 }
 
 impl Slope {
-    fn iter_slope(&self, x_step: usize, y_step: usize) -> IterSlope {
+    /// Read a map whose squares can be any kind of terrain in the legend.
+    fn with_legend(s: &str, legend: Legend) -> Result<Slope> {
+        let mut trees: Vec<Row> = vec![];
+        let mut rows: Vec<Vec<u8>> = vec![];
+        for line in s.lines() {
+            let mut row = Row::new();
+            let mut kinds = vec![];
+            for c in line.chars() {
+                let index = legend
+                    .index(c)
+                    .ok_or_else(|| anyhow!("Char not in legend: {}", c))?;
+                let index = u8::try_from(index)
+                    .map_err(|_| anyhow!("Too many kinds of terrain in the legend"))?;
+                kinds.push(index);
+                row.push(c == '#');
+            }
+            trees.push(row);
+            rows.push(kinds);
+        }
+
+        Ok(Slope {
+            trees,
+            terrain: Some(Terrain { legend, rows }),
+        })
+    }
+
+    /// The symbol drawn for the square at (x, y), wrapping around horizontally.
+    fn symbol(&self, x: isize, y: usize) -> char {
+        let row = &self.trees[y];
+        let x = x.rem_euclid(row.len().max(1) as isize) as usize;
+        match &self.terrain {
+            Some(terrain) => terrain.rows[y]
+                .get(x)
+                .map_or('.', |i| terrain.legend.kinds[*i as usize].symbol),
+            None if row.get(x) == Some(true) => '#',
+            None => '.',
+        }
+    }
+
+    /// The cost of the path, using the legend the map was read with, or counting each tree as 1
+    /// for plain maps.
+    fn cost(&self, start: usize, step: Step) -> Cost {
+        let default = Legend::default();
+        let legend = self.terrain.as_ref().map_or(&default, |t| &t.legend);
+        let mut cost = Cost::new(legend);
+
+        let mut x = start as isize;
+        for y in (0..self.trees.len()).step_by(step.dy.max(1)) {
+            let symbol = self.symbol(x, y);
+            if let Some(index) = legend.index(symbol) {
+                cost.add(index);
+            }
+            x += step.dx;
+        }
+
+        cost
+    }
+
+    /// The width of the map, if every row is the same width.
+    fn width(&self) -> Option<usize> {
+        let width = self.trees.first()?.len();
+        if self.trees.iter().all(|row| row.len() == width) {
+            Some(width)
+        } else {
+            None
+        }
+    }
+
+    fn iter_slope(&self, x_step: usize, y_step: usize) -> IterSlope<'_> {
+        self.iter_step(
+            0,
+            Step {
+                dx: x_step as isize,
+                dy: y_step,
+            },
+        )
+    }
+
+    /// Count the trees on each (right, down) slope from the top left, in a single pass down the
+    /// map.
+    fn count_many(&self, steps: &[(isize, usize)]) -> Vec<usize> {
+        let mut counts = vec![0; steps.len()];
+        let mut xs: Vec<isize> = vec![0; steps.len()];
+
+        for (y, row) in self.trees.iter().enumerate() {
+            let width = row.len().max(1) as isize;
+            for (i, (dx, dy)) in steps.iter().enumerate() {
+                if y % dy.max(&1) != 0 {
+                    continue;
+                }
+                if row.get(xs[i].rem_euclid(width) as usize) == Some(true) {
+                    counts[i] += 1;
+                }
+                xs[i] += dx;
+            }
+        }
+
+        counts
+    }
+
+    /// Walk down from column `start` of the top row, wrapping around in either direction.
+    fn iter_step(&self, start: usize, step: Step) -> IterSlope<'_> {
         IterSlope {
             slope: self,
-            x: 0,
+            x: start as isize,
             y: 0,
-            x_step,
-            y_step,
+            x_step: step.dx,
+            y_step: step.dy,
         }
     }
 }
 
 struct IterSlope<'a> {
     slope: &'a Slope,
-    x: usize,
+    x: isize,
     y: usize,
-    x_step: usize,
+    x_step: isize,
     y_step: usize,
 }
 
@@ -56,21 +310,14 @@ impl<'a> Iterator for IterSlope<'a> {
 
     fn next(&mut self) -> Option<bool> {
         let result = if let Some(next_row) = self.slope.trees.get(self.y) {
-            // Wrap next-row if necessary
-            let x = match self.x {
-                0 => 0,
-                n => n % next_row.len(),
-            };
-            if next_row[x] {
-                Some(true)
-            } else {
-                Some(false)
-            }
+            // Wrap next-row if necessary, to the left as well as the right
+            let x = self.x.rem_euclid(next_row.len().max(1) as isize) as usize;
+            Some(next_row.get(x).unwrap_or(false))
         } else {
             None
         };
 
-        self.y += self.y_step;
+        self.y += self.y_step.max(1);
         self.x += self.x_step;
 
         result
@@ -83,27 +330,207 @@ fn part1(slope: &Slope) -> Result<()> {
     Ok(())
 }
 
+fn part2_steps() -> Vec<(isize, usize)> {
+    SLOPES.iter().map(|(x, y)| (*x as isize, *y)).collect()
+}
+
 fn part2(slope: &Slope) -> Result<()> {
-    let slopes: Vec<(usize, usize)> = vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+    let product: usize = slope.count_many(&part2_steps()).iter().product();
+
+    println!("Part 2: {}", product);
+    Ok(())
+}
+
+// The map, repeated to the right as far as the path goes. Palette indices: open, tree, open on
+// the path, tree on the path.
+fn path_image(slope: &Slope, x_step: usize, y_step: usize) -> Image {
+    let height = slope.trees.len();
+    let map_width = slope.trees.first().map_or(1, |row| row.len().max(1));
+    let path_width = x_step * (height.saturating_sub(1) / y_step.max(1)) + 1;
+    let width = path_width.div_ceil(map_width) * map_width;
+
+    let mut image = Image::from_fn(width, height, |x, y| {
+        slope.trees[y]
+            .get(x % map_width)
+            .map_or(0, |tree| tree as usize)
+    });
+    for (x, y) in (0..height)
+        .step_by(y_step.max(1))
+        .enumerate()
+        .map(|(i, y)| (i * x_step, y))
+    {
+        image.set(x, y, image.get(x, y) + 2);
+    }
+
+    image
+}
+
+fn export_frames(slope: &Slope, mut frames: FrameWriter) -> Result<()> {
+    for (x_step, y_step) in SLOPES.iter() {
+        let path = frames.write(&path_image(slope, *x_step, *y_step))?;
+        println!("Right {}, down {}: {}", x_step, y_step, path.display());
+    }
+
+    Ok(())
+}
+
+fn search(slope: &Slope, start: usize, bounds: Bounds, top: usize) {
+    let now = Instant::now();
+    let ranking = search::rank(slope, start, bounds);
+    println!(
+        "Searched {} slopes from column {}, took {:#?}",
+        ranking.len(),
+        start,
+        now.elapsed()
+    );
 
-    let product: u32 = slopes
+    let steps = |steps: &[Step]| {
+        steps
+            .iter()
+            .map(|s| format!("({}, {})", s.dx, s.dy))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+    if let Some((fewest, most)) = search::extremes(&ranking) {
+        println!("Fewest trees: {} on {}", fewest.trees, steps(&fewest.steps));
+        println!("Most trees: {} on {}", most.trees, steps(&most.steps));
+    }
+
+    println!("Most trees first:");
+    for (step, trees) in ranking.iter().rev().take(top) {
+        println!("    right {}, down {}: {}", step.dx, step.dy, trees);
+    }
+    println!("Fewest trees first:");
+    for (step, trees) in ranking.iter().take(top) {
+        println!("    right {}, down {}: {}", step.dx, step.dy, trees);
+    }
+}
+
+fn parse_density(s: &str) -> Result<i64> {
+    let density = s.parse()?;
+    if !(0..=100).contains(&density) {
+        return Err(anyhow!(
+            "Density must be a percentage from 0 to 100, not {}",
+            density
+        ));
+    }
+    Ok(density)
+}
+
+// A map of the given size, where each square is a tree with probability `density` percent
+fn generate(rows: usize, width: usize, density: i64, seed: u64) -> Slope {
+    let mut rng = Rng::new(seed);
+    Slope {
+        trees: (0..rows)
+            .map(|_| {
+                let mut row = Row::new();
+                for _ in 0..width {
+                    row.push(rng.range(0, 100) < density);
+                }
+                row
+            })
+            .collect(),
+        terrain: None,
+    }
+}
+
+fn bench(rows: usize, width: usize, density: i64, seed: u64) -> Result<()> {
+    let slope = generate(rows, width, density, seed);
+    println!("Generated a {}x{} map", width, rows);
+
+    let now = Instant::now();
+    let separate: Vec<usize> = SLOPES
         .iter()
-        .map(|(x_step, y_step)| {
-            slope
-                .iter_slope(*x_step, *y_step)
-                .map(|b| b as u32)
-                .sum::<u32>()
-        })
-        .product();
+        .map(|(x_step, y_step)| slope.iter_slope(*x_step, *y_step).filter(|t| *t).count())
+        .collect();
+    println!(
+        "iter_slope per slope: {:?}, took {:#?}",
+        separate,
+        now.elapsed()
+    );
 
-    println!("Part 2: {}", product);
+    let now = Instant::now();
+    let single = slope.count_many(&part2_steps());
+    println!(
+        "count_many:           {:?}, took {:#?}",
+        single,
+        now.elapsed()
+    );
+
+    if separate != single {
+        return Err(anyhow!(
+            "iter_slope counted {:?} trees, but count_many counted {:?}",
+            separate,
+            single
+        ));
+    }
     Ok(())
 }
 
+fn print_cost(slope: &Slope, start: usize, step: Step) {
+    let cost = slope.cost(start, step);
+    let breakdown: Vec<String> = cost
+        .breakdown
+        .iter()
+        .map(|(kind, count)| format!("{} {} x {}", count, kind.name, kind.cost))
+        .collect();
+    println!(
+        "Cost of right {}, down {}, from column {}: {} ({})",
+        step.dx,
+        step.dy,
+        start,
+        cost.total,
+        breakdown.join(", ")
+    );
+}
+
 fn main() -> Result<()> {
+    let opt = Opt::from_args();
+
+    if let Some(Command::Bench {
+        rows,
+        width,
+        density,
+        seed,
+    }) = opt.cmd
+    {
+        return bench(rows, width, density, seed);
+    }
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
-    let slope: Slope = input.parse()?;
+    let slope: Slope = match &opt.legend {
+        Some(path) => Slope::with_legend(&input, fs::read_to_string(path)?.parse()?)?,
+        None => input.parse()?,
+    };
+
+    if let Some(Command::Search {
+        min_right,
+        max_right,
+        max_down,
+        top,
+    }) = opt.cmd
+    {
+        let bounds = Bounds {
+            min_right,
+            max_right,
+            max_down,
+        };
+        search(&slope, opt.start, bounds, top);
+        return Ok(());
+    }
+
+    if let Some(Command::Route { moves, from }) = &opt.cmd {
+        let moves = if moves.is_empty() {
+            &route::DEFAULT_MOVES[..]
+        } else {
+            moves
+        };
+        let route = route::cheapest(&slope, moves, *from)?;
+        println!("Cheapest route: {}", route.cost);
+        print!("{}", route::draw(&slope, &route));
+        return Ok(());
+    }
 
     let now = Instant::now();
     part1(&slope)?;
@@ -112,5 +539,244 @@ fn main() -> Result<()> {
     part2(&slope)?;
     println!("Part 2 took: {:#?}", now.elapsed());
 
+    for step in &opt.slopes {
+        let count = slope.iter_step(opt.start, *step).filter(|t| *t).count();
+        println!(
+            "Right {}, down {}, from column {}: {} trees",
+            step.dx, step.dy, opt.start, count
+        );
+    }
+
+    if opt.legend.is_some() {
+        print_cost(&slope, 0, Step { dx: 3, dy: 1 });
+        for step in &opt.slopes {
+            print_cost(&slope, opt.start, *step);
+        }
+    }
+
+    if let Some(step) = opt.render {
+        print!("{}", PathView::new(&slope, opt.start, step));
+    }
+
+    if let Some(frames) = opt.frames.writer("slope", PALETTE, 2)? {
+        export_frames(&slope, frames)?;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    fn count(slope: &Slope, start: usize, step: &str) -> Result<usize> {
+        Ok(slope.iter_step(start, step.parse()?).filter(|t| *t).count())
+    }
+
+    #[test]
+    fn test_steps() -> Result<()> {
+        let slope: Slope = EXAMPLE.parse()?;
+
+        assert_eq!(7, slope.iter_slope(3, 1).filter(|t| *t).count());
+        assert_eq!(7, count(&slope, 0, "3,1")?);
+
+        // Going left wraps around to the right hand edge
+        assert_eq!(Step { dx: -3, dy: 1 }, "-3,1".parse()?);
+        assert_eq!(3, count(&slope, 0, "-3,1")?);
+
+        // 2 right per 4 down only passes exactly through every other row
+        assert_eq!(Step { dx: 1, dy: 2 }, "2/4".parse()?);
+        assert_eq!(count(&slope, 0, "1,2")?, count(&slope, 0, "2/4")?);
+        assert_eq!(Step { dx: 2, dy: 4 }, "2,4".parse()?);
+
+        assert_eq!(4, count(&slope, 2, "0,1")?);
+        assert!("1,0".parse::<Step>().is_err());
+        assert!("1".parse::<Step>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_options() -> Result<()> {
+        // Leftward slopes don't need an '=' to tell them from options
+        let opt = Opt::from_iter_safe(&["aoc03", "--slope", "-1,2", "--slope", "3,1"])?;
+        assert_eq!(
+            vec![Step { dx: -1, dy: 2 }, Step { dx: 3, dy: 1 }],
+            opt.slopes
+        );
+        let opt = Opt::from_iter_safe(&["aoc03", "--render", "-1,2"])?;
+        assert_eq!(Some(Step { dx: -1, dy: 2 }), opt.render);
+
+        assert!(Opt::from_iter_safe(&["aoc03", "bench", "--density", "100"]).is_ok());
+        assert!(Opt::from_iter_safe(&["aoc03", "bench", "--density", "101"]).is_err());
+        assert!(Opt::from_iter_safe(&["aoc03", "bench", "--density=-1"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_many() -> Result<()> {
+        let slope: Slope = EXAMPLE.parse()?;
+
+        assert_eq!(vec![2, 7, 3, 4, 2], slope.count_many(&part2_steps()));
+        assert_eq!(vec![3, 7], slope.count_many(&[(-3, 1), (3, 1)]));
+        assert_eq!(Some(true), slope.trees[0].get(2));
+        assert_eq!(None, slope.trees[0].get(11));
+
+        // A single pass counts the same trees as walking each slope, over rows of several words
+        let slope = generate(500, 200, 20, 2020);
+        let steps = [
+            (1, 1),
+            (3, 1),
+            (5, 1),
+            (7, 1),
+            (1, 2),
+            (-3, 1),
+            (0, 3),
+            (130, 2),
+        ];
+        let separate: Vec<usize> = steps
+            .iter()
+            .map(|(dx, dy)| {
+                let step = Step { dx: *dx, dy: *dy };
+                slope.iter_step(0, step).filter(|t| *t).count()
+            })
+            .collect();
+        assert_eq!(separate, slope.count_many(&steps));
+
+        Ok(())
+    }
+
+    // Run with `cargo test --release -- --ignored --nocapture` to see the timings
+    #[test]
+    #[ignore]
+    fn bench_count_many() -> Result<()> {
+        bench(1_000_000, 31, 20, 2020)
+    }
+
+    #[test]
+    fn test_render() -> Result<()> {
+        let slope: Slope = EXAMPLE.parse()?;
+
+        let rendered = PathView::new(&slope, 0, "3,1".parse()?).to_string();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(11, lines.len());
+        assert_eq!("O.##.........##.........##.......", lines[0]);
+        assert_eq!("#..O#...#..#...#...#..#...#...#..", lines[1]);
+        assert_eq!(".#..#...#.#.#..#...#.#.#..#...X.#", lines[10]);
+        assert_eq!(7, rendered.matches('X').count());
+
+        // Going left adds copies of the map to the left
+        let rendered = PathView::new(&slope, 0, "-1,2".parse()?).to_string();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(22, lines[0].len());
+        assert_eq!("..##.......O.##.......", lines[0]);
+        assert_eq!(".#....#..#O.#....#..#.", lines[2]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_terrain() -> Result<()> {
+        let legend: Legend = "// Snowy slopes\n. open 0\n# tree 1\n^ rock 5\n* snow 2".parse()?;
+        let slope = Slope::with_legend("..^\n#*.\n.^#\n*.#", legend)?;
+
+        // Rocks and snow aren't trees
+        assert_eq!(vec![1, 1], slope.count_many(&[(1, 1), (0, 1)]));
+
+        let cost = slope.cost(0, Step { dx: 1, dy: 1 });
+        assert_eq!(5, cost.total);
+        let counts: Vec<usize> = cost.breakdown.iter().map(|(_, n)| *n).collect();
+        assert_eq!(vec![1, 1, 0, 2], counts);
+
+        // Plain maps cost one per tree
+        let slope: Slope = EXAMPLE.parse()?;
+        assert_eq!(7, slope.cost(0, Step { dx: 3, dy: 1 }).total);
+        assert!(Slope::with_legend("..x", Legend::default()).is_err());
+        assert!("# tree 1\n# rock 2".parse::<Legend>().is_err());
+
+        // Squares past the 256th kind of terrain don't fit in a byte
+        let symbols: Vec<char> = (0..300).filter_map(|i| char::from_u32(0x100 + i)).collect();
+        let lines: Vec<String> = symbols.iter().map(|c| format!("{} kind 1", c)).collect();
+        let legend: Legend = lines.join("\n").parse()?;
+        assert!(
+            Slope::with_legend(&symbols[..256].iter().collect::<String>(), legend.clone()).is_ok()
+        );
+        assert!(Slope::with_legend(&symbols[256].to_string(), legend).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_route() -> Result<()> {
+        let slope: Slope = EXAMPLE.parse()?;
+
+        // There is a clear run down from the top
+        let route = route::cheapest(&slope, &route::DEFAULT_MOVES, None)?;
+        assert_eq!(0, route.cost);
+        assert_eq!(11, route.squares.len());
+        assert!(route
+            .squares
+            .iter()
+            .all(|(x, y)| slope.symbol(*x as isize, *y) == '.'));
+
+        // Straight down from column 3 hits trees, as the route must take them into account
+        let down = vec![Step { dx: 0, dy: 1 }];
+        let route = route::cheapest(&slope, &down, Some(3))?;
+        assert_eq!(
+            slope.iter_step(3, down[0]).filter(|t| *t).count() as u64,
+            route.cost
+        );
+
+        // Knight-like jumps skip rows, but must land on the bottom one
+        let knight = vec![Step { dx: 1, dy: 2 }, Step { dx: -1, dy: 2 }];
+        assert_eq!(6, route::cheapest(&slope, &knight, Some(0))?.squares.len());
+        let evens = vec![Step { dx: 0, dy: 2 }, Step { dx: 0, dy: 4 }];
+        assert!(route::cheapest(&slope, &evens, Some(0)).is_ok());
+        let odd = vec![Step { dx: 0, dy: 3 }];
+        assert!(route::cheapest(&slope, &odd, Some(0)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search() -> Result<()> {
+        let slope: Slope = EXAMPLE.parse()?;
+        let bounds = Bounds {
+            min_right: -12,
+            max_right: 12,
+            max_down: 3,
+        };
+        let ranking = search::rank(&slope, 0, bounds);
+
+        assert_eq!(75, ranking.len());
+        for (step, trees) in &ranking {
+            assert_eq!(*trees, slope.iter_step(0, *step).filter(|t| *t).count());
+        }
+        assert!(ranking.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        // -8 and 3 are the same step on a map 11 squares wide
+        let (fewest, most) = search::extremes(&ranking).unwrap();
+        assert_eq!(0, fewest.trees);
+        assert!(fewest.steps.contains(&ranking[0].0));
+        assert_eq!(7, most.trees);
+        assert_eq!(
+            vec![Step { dx: -8, dy: 1 }, Step { dx: 3, dy: 1 }],
+            most.steps
+        );
+
+        Ok(())
+    }
+}
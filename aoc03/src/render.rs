@@ -0,0 +1,54 @@
+//! Draw the map with a path overlaid, like the examples in the puzzle text.
+use std::fmt;
+
+use crate::{Slope, Step};
+
+/// The map, repeated horizontally as many times as the path needs, with the squares on the path
+/// drawn as `X` (tree) or `O` (anything else).
+pub struct PathView<'a> {
+    slope: &'a Slope,
+    start: usize,
+    step: Step,
+}
+
+impl<'a> PathView<'a> {
+    pub fn new(slope: &'a Slope, start: usize, step: Step) -> PathView<'a> {
+        PathView { slope, start, step }
+    }
+
+    // The column of the path in row `y`, if it visits that row
+    fn path_x(&self, y: usize) -> Option<isize> {
+        let dy = self.step.dy.max(1);
+        if y % dy == 0 {
+            Some(self.start as isize + self.step.dx * (y / dy) as isize)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for PathView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let height = self.slope.trees.len();
+        let width = self.slope.width().unwrap_or(1).max(1) as isize;
+
+        // Whole copies of the map, covering every column the path visits
+        let xs = (0..height).filter_map(|y| self.path_x(y));
+        let left = xs.clone().min().unwrap_or(0).div_euclid(width) * width;
+        let right = (xs.max().unwrap_or(0).div_euclid(width) + 1) * width;
+
+        for y in 0..height {
+            let on_path = self.path_x(y);
+            let line: String = (left..right)
+                .map(|x| match (Some(x) == on_path, self.slope.symbol(x, y)) {
+                    (true, '#') => 'X',
+                    (true, _) => 'O',
+                    (false, symbol) => symbol,
+                })
+                .collect();
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
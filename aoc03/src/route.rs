@@ -0,0 +1,124 @@
+//! The cheapest free-form route from the top row of the map to the bottom.
+//!
+//! Every move goes down at least one row, so the squares form a directed acyclic graph and the
+//! cheapest route can be found one row at a time.
+use anyhow::{anyhow, Result};
+
+use crate::terrain::Legend;
+use crate::{Slope, Step};
+
+/// Down, down-left and down-right.
+pub const DEFAULT_MOVES: [Step; 3] = [
+    Step { dx: 0, dy: 1 },
+    Step { dx: -1, dy: 1 },
+    Step { dx: 1, dy: 1 },
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    /// The total cost of the squares on the route, including the first
+    pub cost: u64,
+    /// (x, y) of each square on the route, with x wrapped onto the map
+    pub squares: Vec<(usize, usize)>,
+}
+
+/// Find the cheapest route from the top row to the bottom row, moving by any of `moves` each time
+/// and wrapping around horizontally. Starts from column `from` if given, or else from whichever
+/// column is cheapest.
+///
+/// Each square costs as much as its terrain, or one per tree for plain maps.
+pub fn cheapest(slope: &Slope, moves: &[Step], from: Option<usize>) -> Result<Route> {
+    let width = slope
+        .width()
+        .filter(|w| *w > 0)
+        .ok_or_else(|| anyhow!("Routes need a map whose rows are all the same width"))?;
+    let height = slope.trees.len();
+    if moves.iter().any(|m| m.dy == 0) {
+        return Err(anyhow!("Every move must go down"));
+    }
+
+    let default = Legend::default();
+    let legend = slope.terrain.as_ref().map_or(&default, |t| &t.legend);
+    let cost = |x: usize, y: usize| {
+        legend
+            .index(slope.symbol(x as isize, y))
+            .map_or(0, |i| legend.kinds[i].cost)
+    };
+
+    // The cheapest cost of reaching each square, and the square it was reached from
+    let mut best: Vec<Vec<Option<u64>>> = vec![vec![None; width]; height];
+    let mut previous: Vec<Vec<usize>> = vec![vec![0; width]; height];
+    for (x, start) in best[0].iter_mut().enumerate() {
+        if from.is_none_or(|f| f % width == x) {
+            *start = Some(cost(x, 0));
+        }
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            let here = match best[y][x] {
+                Some(c) => c,
+                None => continue,
+            };
+            for m in moves {
+                let (next_x, next_y) = (
+                    (x as isize + m.dx).rem_euclid(width as isize) as usize,
+                    y + m.dy,
+                );
+                if next_y >= height {
+                    continue;
+                }
+                let total = here + cost(next_x, next_y);
+                if best[next_y][next_x].is_none_or(|c| total < c) {
+                    best[next_y][next_x] = Some(total);
+                    previous[next_y][next_x] = y * width + x;
+                }
+            }
+        }
+    }
+
+    let (end, total) = best
+        .last()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(x, c)| c.map(|c| (x, c)))
+        .min_by_key(|(_, c)| *c)
+        .ok_or_else(|| anyhow!("No route reaches the bottom row"))?;
+
+    // Walk back up to the top row
+    let mut squares = vec![(end, height - 1)];
+    while let Some(&(x, y)) = squares.last() {
+        if y == 0 {
+            break;
+        }
+        let p = previous[y][x];
+        squares.push((p % width, p / width));
+    }
+    squares.reverse();
+
+    Ok(Route {
+        cost: total,
+        squares,
+    })
+}
+
+/// The map with the squares on the route drawn as `X` (tree) or `O` (anything else).
+pub fn draw(slope: &Slope, route: &Route) -> String {
+    let mut lines: Vec<Vec<char>> = (0..slope.trees.len())
+        .map(|y| {
+            (0..slope.trees[y].len())
+                .map(|x| slope.symbol(x as isize, y))
+                .collect()
+        })
+        .collect();
+    for (x, y) in &route.squares {
+        let square = &mut lines[*y][*x];
+        *square = if *square == '#' { 'X' } else { 'O' };
+    }
+
+    lines
+        .into_iter()
+        .map(|line| line.into_iter().collect::<String>() + "\n")
+        .collect()
+}
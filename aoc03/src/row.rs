@@ -0,0 +1,38 @@
+//! A row of the map, packed one bit per square.
+
+const BITS: usize = 64;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Row {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Row {
+    pub fn new() -> Row {
+        Row::default()
+    }
+
+    pub fn push(&mut self, tree: bool) {
+        if self.len % BITS == 0 {
+            self.words.push(0);
+        }
+        if tree {
+            self.words[self.len / BITS] |= 1 << (self.len % BITS);
+        }
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there is a tree at `x`, or None if `x` is past the end of the row.
+    pub fn get(&self, x: usize) -> Option<bool> {
+        if x < self.len {
+            Some(self.words[x / BITS] >> (x % BITS) & 1 == 1)
+        } else {
+            None
+        }
+    }
+}
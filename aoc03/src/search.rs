@@ -0,0 +1,56 @@
+//! Rank every slope within some bounds by the number of trees it hits.
+use std::collections::HashMap;
+
+use crate::{Slope, Step};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min_right: isize,
+    pub max_right: isize,
+    pub max_down: usize,
+}
+
+/// Count the trees on every slope with `min_right <= dx <= max_right` and `1 <= dy <= max_down`,
+/// starting from column `start`. Sorted by the number of trees, fewest first, then by step.
+///
+/// If every row is the same width, steps that differ by a multiple of it visit the same squares,
+/// so only one of them is walked.
+pub fn rank(slope: &Slope, start: usize, bounds: Bounds) -> Vec<(Step, usize)> {
+    let width = slope.width();
+    let mut counts: HashMap<(isize, usize), usize> = HashMap::new();
+    let mut ranking = vec![];
+
+    for dy in 1..=bounds.max_down {
+        for dx in bounds.min_right..=bounds.max_right {
+            let step = Step { dx, dy };
+            let trees = *counts
+                .entry((width.map_or(dx, |w| dx.rem_euclid(w as isize)), dy))
+                .or_insert_with(|| slope.iter_step(start, step).filter(|t| *t).count());
+            ranking.push((step, trees));
+        }
+    }
+
+    ranking.sort_by_key(|(step, trees)| (*trees, step.dy, step.dx));
+    ranking
+}
+
+/// A number of trees, and every slope that hits exactly that many.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extreme {
+    pub trees: usize,
+    pub steps: Vec<Step>,
+}
+
+/// The slopes hitting the fewest and the most trees.
+pub fn extremes(ranking: &[(Step, usize)]) -> Option<(Extreme, Extreme)> {
+    let with = |trees: usize| Extreme {
+        trees,
+        steps: ranking
+            .iter()
+            .filter(|(_, t)| *t == trees)
+            .map(|(step, _)| *step)
+            .collect(),
+    };
+
+    Some((with(ranking.first()?.1), with(ranking.last()?.1)))
+}
@@ -0,0 +1,111 @@
+//! Maps with several kinds of terrain, each with a cost for passing through it.
+//!
+//! A legend has one line per kind of terrain: its symbol, name and cost, e.g.
+//!
+//! ```text
+//! . open 0
+//! # tree 1
+//! ^ rock 5
+//! * snow 2
+//! ```
+//!
+//! Lines starting with `//` are comments.
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kind {
+    pub symbol: char,
+    pub name: String,
+    pub cost: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Legend {
+    pub kinds: Vec<Kind>,
+}
+
+impl Legend {
+    /// The index of the kind of terrain drawn as `symbol`.
+    pub fn index(&self, symbol: char) -> Option<usize> {
+        self.kinds.iter().position(|k| k.symbol == symbol)
+    }
+}
+
+/// Open squares and trees, where each tree costs 1.
+impl Default for Legend {
+    fn default() -> Legend {
+        Legend {
+            kinds: vec![
+                Kind {
+                    symbol: '.',
+                    name: "open".to_string(),
+                    cost: 0,
+                },
+                Kind {
+                    symbol: '#',
+                    name: "tree".to_string(),
+                    cost: 1,
+                },
+            ],
+        }
+    }
+}
+
+impl FromStr for Legend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Legend> {
+        let mut kinds: Vec<Kind> = vec![];
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let kind = match fields.as_slice() {
+                [symbol, name, cost] if symbol.chars().count() == 1 => Kind {
+                    symbol: symbol.chars().next().unwrap(),
+                    name: name.to_string(),
+                    cost: cost.parse()?,
+                },
+                _ => return Err(anyhow!("Invalid legend line: \"{}\"", line)),
+            };
+            if kinds.iter().any(|k| k.symbol == kind.symbol) {
+                return Err(anyhow!("Symbol '{}' is in the legend twice", kind.symbol));
+            }
+            kinds.push(kind);
+        }
+
+        Ok(Legend { kinds })
+    }
+}
+
+/// The kind of terrain of every square, as indices into the legend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Terrain {
+    pub legend: Legend,
+    pub rows: Vec<Vec<u8>>,
+}
+
+/// The cost of a path, and how many squares of each kind of terrain it crossed, in legend order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cost {
+    pub total: u64,
+    pub breakdown: Vec<(Kind, usize)>,
+}
+
+impl Cost {
+    pub fn new(legend: &Legend) -> Cost {
+        Cost {
+            total: 0,
+            breakdown: legend.kinds.iter().map(|k| (k.clone(), 0)).collect(),
+        }
+    }
+
+    pub fn add(&mut self, index: usize) {
+        let (kind, count) = &mut self.breakdown[index];
+        self.total += kind.cost;
+        *count += 1;
+    }
+}
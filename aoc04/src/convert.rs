@@ -0,0 +1,185 @@
+//! Read and write records in the puzzle's format, JSON Lines or CSV.
+//!
+//! Every field is kept, in order, whether or not it is one a passport knows about, so records
+//! round-trip between the formats. A record that a format can't represent, such as a value with a
+//! space in the puzzle's format, is an error rather than being written differently.
+use std::io::Write;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+use serde_json::{Map, Value};
+
+use crate::{records, Record};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Puzzle,
+    Jsonl,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Format> {
+        match s {
+            "puzzle" => Ok(Format::Puzzle),
+            "jsonl" => Ok(Format::Jsonl),
+            "csv" => Ok(Format::Csv),
+            _ => Err(anyhow!("Unknown record format: \"{}\"", s)),
+        }
+    }
+}
+
+pub fn read(input: &str, format: Format) -> Result<Vec<Record>> {
+    match format {
+        Format::Puzzle => Ok(records(input)),
+        Format::Jsonl => read_jsonl(input),
+        Format::Csv => read_csv(input),
+    }
+}
+
+// One object per line, whose values are strings or numbers. Null values are absent fields.
+fn read_jsonl(input: &str) -> Result<Vec<Record>> {
+    let mut records = vec![];
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let object: Map<String, Value> = serde_json::from_str(line)
+            .map_err(|e| anyhow!("Line {} is not a JSON object: {}", i + 1, e))?;
+
+        let mut fields = vec![];
+        for (key, value) in object {
+            let value = match value {
+                Value::Null => continue,
+                Value::String(s) => s,
+                Value::Number(n) => n.to_string(),
+                _ => return Err(anyhow!("Line {}: {} is not a string", i + 1, key)),
+            };
+            fields.push((key, value));
+        }
+        records.push(Record {
+            first_line: i + 1,
+            last_line: i + 1,
+            fields,
+        });
+    }
+
+    Ok(records)
+}
+
+// A header row of keys, then one row per record. Empty cells are absent fields.
+fn read_csv(input: &str) -> Result<Vec<Record>> {
+    let mut csv = csv::Reader::from_reader(input.as_bytes());
+    let header = csv.headers()?.clone();
+
+    csv.records()
+        .map(|row| {
+            let row = row?;
+            // Quoted values can span several lines
+            let first_line = row.position().map_or(1, |p| p.line() as usize);
+            let last_line = first_line + row.iter().map(|v| v.matches('\n').count()).sum::<usize>();
+            Ok(Record {
+                first_line,
+                last_line,
+                fields: header
+                    .iter()
+                    .zip(row.iter())
+                    .filter(|(_, value)| !value.is_empty())
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+// Check that reading `record` back after writing it as `format` gives the same fields
+fn check_round_trip(record: &Record, format: Format) -> Result<()> {
+    let problem = |message: String| Err(anyhow!("Line {}: {}", record.first_line, message));
+
+    if format == Format::Puzzle && record.fields.is_empty() {
+        return problem("the record has no fields".to_string());
+    }
+    for (i, (key, value)) in record.fields.iter().enumerate() {
+        match format {
+            Format::Puzzle if key.contains(|c: char| c == ':' || c.is_whitespace()) => {
+                return problem(format!("the key \"{}\" contains ':' or whitespace", key))
+            }
+            Format::Puzzle if value.contains(char::is_whitespace) => {
+                return problem(format!("the value of {} contains whitespace", key))
+            }
+            Format::Csv if value.is_empty() => {
+                return problem(format!(
+                    "{} is empty, which CSV can't tell from absent",
+                    key
+                ))
+            }
+            Format::Jsonl | Format::Csv if record.fields[..i].iter().any(|(k, _)| k == key) => {
+                return problem(format!("{} appears more than once", key))
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Write every record, or nothing if any of them can't be read back the same way.
+pub fn write<W: Write>(out: &mut W, records: &[Record], format: Format) -> Result<()> {
+    for record in records {
+        check_round_trip(record, format)?;
+    }
+
+    match format {
+        Format::Puzzle => {
+            for (i, record) in records.iter().enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                let pairs: Vec<String> = record
+                    .fields
+                    .iter()
+                    .map(|(key, value)| format!("{}:{}", key, value))
+                    .collect();
+                writeln!(out, "{}", pairs.join(" "))?;
+            }
+        }
+        Format::Jsonl => {
+            for record in records {
+                let object: Map<String, Value> = record
+                    .fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+                    .collect();
+                writeln!(out, "{}", serde_json::to_string(&object)?)?;
+            }
+        }
+        Format::Csv => {
+            // Every key of every record, in the order they first appear
+            let mut header: Vec<&str> = vec![];
+            for (key, _) in records.iter().flat_map(|r| &r.fields) {
+                if !header.contains(&key.as_str()) {
+                    header.push(key);
+                }
+            }
+
+            // Like the other formats, no records is no output
+            if header.is_empty() {
+                return Ok(());
+            }
+
+            let mut csv = csv::Writer::from_writer(&mut *out);
+            csv.write_record(&header)?;
+            for record in records {
+                csv.write_record(header.iter().map(|key| {
+                    let value = record.fields.iter().find(|(k, _)| k == key);
+                    value.map_or("", |(_, v)| v.as_str())
+                }))?;
+            }
+            csv.flush()?;
+        }
+    }
+
+    Ok(())
+}
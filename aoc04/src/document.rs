@@ -0,0 +1,95 @@
+//! Named kinds of document, each with the fields it requires, e.g.
+//!
+//! ```text
+//! passport: byr iyr eyr hgt hcl ecl pid cid
+//! north-pole-credential: byr iyr eyr hgt hcl ecl pid
+//! ```
+//!
+//! A record must have the fields its type requires as well as those the schema requires, so a type
+//! can add required fields but not remove them. Lines starting with `//` are comments. The kinds
+//! of the puzzle are in `rules/documents.txt`.
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+
+use crate::Passport;
+
+/// The document types used when no others are given.
+const DEFAULT: &str = include_str!("../rules/documents.txt");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentType {
+    pub name: String,
+    pub required: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentTypes {
+    pub types: Vec<DocumentType>,
+}
+
+impl DocumentType {
+    /// The required fields that the passport doesn't have.
+    pub fn missing(&self, passport: &Passport) -> Vec<String> {
+        self.required
+            .iter()
+            .filter(|f| passport.get(f).is_none())
+            .cloned()
+            .collect()
+    }
+}
+
+impl DocumentTypes {
+    /// The index of the first type whose required fields the passport all has.
+    pub fn position(&self, passport: &Passport) -> Option<usize> {
+        self.types
+            .iter()
+            .position(|t| t.required.iter().all(|f| passport.get(f).is_some()))
+    }
+
+    /// The first type whose required fields the passport all has.
+    pub fn classify(&self, passport: &Passport) -> Option<&DocumentType> {
+        self.position(passport).map(|i| &self.types[i])
+    }
+
+    /// The first of the types the passport is missing the fewest required fields of, which is
+    /// its type if it has one.
+    pub fn closest(&self, passport: &Passport) -> Option<&DocumentType> {
+        self.types.iter().min_by_key(|t| t.missing(passport).len())
+    }
+}
+
+impl Default for DocumentTypes {
+    fn default() -> DocumentTypes {
+        DEFAULT
+            .parse()
+            .expect("the default document types are valid")
+    }
+}
+
+impl FromStr for DocumentTypes {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<DocumentTypes> {
+        let mut types: Vec<DocumentType> = vec![];
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let (name, fields) = line
+                .split_once(':')
+                .filter(|(name, _)| !name.trim().is_empty())
+                .ok_or_else(|| anyhow!("Invalid document type: \"{}\"", line))?;
+            let name = name.trim().to_string();
+            if types.iter().any(|t| t.name == name) {
+                return Err(anyhow!("Document type {} is defined twice", name));
+            }
+            types.push(DocumentType {
+                name,
+                required: fields.split_whitespace().map(String::from).collect(),
+            });
+        }
+
+        Ok(DocumentTypes { types })
+    }
+}
@@ -0,0 +1,193 @@
+//! Typed passport fields, each converted from its raw value with `TryFrom`.
+//!
+//! Every passport has the seven fields of the puzzle other than `cid`, each in its fixed format. A
+//! schema can require more fields or narrow the ranges and allowed values, but can't make any of
+//! these optional or widen their formats, e.g. to allow other eye colors.
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+use crate::Passport;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldError {
+    Missing(&'static str),
+    Year(String),
+    Height(String),
+    HairColor(String),
+    EyeColor(String),
+    PassportId(String),
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldError::Missing(key) => write!(f, "Missing required field {}", key),
+            FieldError::Year(s) => write!(f, "\"{}\" is not a four-digit year", s),
+            FieldError::Height(s) => write!(f, "\"{}\" is not a height in cm or in", s),
+            FieldError::HairColor(s) => write!(f, "\"{}\" is not a color like #1a2b3c", s),
+            FieldError::EyeColor(s) => write!(f, "\"{}\" is not an eye color", s),
+            FieldError::PassportId(s) => write!(f, "\"{}\" is not a nine-digit passport ID", s),
+        }
+    }
+}
+
+impl error::Error for FieldError {}
+
+fn all_digits(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Year(pub u16);
+
+impl TryFrom<&str> for Year {
+    type Error = FieldError;
+
+    fn try_from(s: &str) -> Result<Year, FieldError> {
+        match s.parse() {
+            Ok(year) if s.len() == 4 && all_digits(s) => Ok(Year(year)),
+            _ => Err(FieldError::Year(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Height {
+    Cm(u32),
+    In(u32),
+}
+
+impl TryFrom<&str> for Height {
+    type Error = FieldError;
+
+    fn try_from(s: &str) -> Result<Height, FieldError> {
+        let err = || FieldError::Height(s.to_string());
+        if s.len() < 2 || !s.is_char_boundary(s.len() - 2) {
+            return Err(err());
+        }
+        let (number, unit) = s.split_at(s.len() - 2);
+        if !all_digits(number) {
+            return Err(err());
+        }
+        let n = number.parse().map_err(|_| err())?;
+        match unit {
+            "cm" => Ok(Height::Cm(n)),
+            "in" => Ok(Height::In(n)),
+            _ => Err(err()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HairColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl TryFrom<&str> for HairColor {
+    type Error = FieldError;
+
+    fn try_from(s: &str) -> Result<HairColor, FieldError> {
+        let err = || FieldError::HairColor(s.to_string());
+        let hex = s.strip_prefix('#').ok_or_else(err)?;
+        if hex.len() != 6 || !hex.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')) {
+            return Err(err());
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| err());
+
+        Ok(HairColor {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Hazel,
+    Other,
+}
+
+impl TryFrom<&str> for EyeColor {
+    type Error = FieldError;
+
+    fn try_from(s: &str) -> Result<EyeColor, FieldError> {
+        match s {
+            "amb" => Ok(EyeColor::Amber),
+            "blu" => Ok(EyeColor::Blue),
+            "brn" => Ok(EyeColor::Brown),
+            "gry" => Ok(EyeColor::Gray),
+            "grn" => Ok(EyeColor::Green),
+            "hzl" => Ok(EyeColor::Hazel),
+            "oth" => Ok(EyeColor::Other),
+            _ => Err(FieldError::EyeColor(s.to_string())),
+        }
+    }
+}
+
+/// Nine digits, including any leading zeroes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassportId(pub String);
+
+impl TryFrom<&str> for PassportId {
+    type Error = FieldError;
+
+    fn try_from(s: &str) -> Result<PassportId, FieldError> {
+        if s.len() == 9 && all_digits(s) {
+            Ok(PassportId(s.to_string()))
+        } else {
+            Err(FieldError::PassportId(s.to_string()))
+        }
+    }
+}
+
+/// Check that the value of a known field parses into its type. Other fields can be anything.
+pub fn check(key: &str, value: &str) -> Result<(), FieldError> {
+    match key {
+        "byr" | "iyr" | "eyr" => Year::try_from(value).map(|_| ()),
+        "hgt" => Height::try_from(value).map(|_| ()),
+        "hcl" => HairColor::try_from(value).map(|_| ()),
+        "ecl" => EyeColor::try_from(value).map(|_| ()),
+        "pid" => PassportId::try_from(value).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedPassport {
+    pub byr: Year,
+    pub iyr: Year,
+    pub eyr: Year,
+    pub hgt: Height,
+    pub hcl: HairColor,
+    pub ecl: EyeColor,
+    pub pid: PassportId,
+    pub cid: Option<String>,
+}
+
+impl TryFrom<&Passport> for TypedPassport {
+    type Error = FieldError;
+
+    fn try_from(p: &Passport) -> Result<TypedPassport, FieldError> {
+        let field = |key: &'static str| p.get(key).ok_or(FieldError::Missing(key));
+
+        Ok(TypedPassport {
+            byr: Year::try_from(field("byr")?)?,
+            iyr: Year::try_from(field("iyr")?)?,
+            eyr: Year::try_from(field("eyr")?)?,
+            hgt: Height::try_from(field("hgt")?)?,
+            hcl: HairColor::try_from(field("hcl")?)?,
+            ecl: EyeColor::try_from(field("ecl")?)?,
+            pid: PassportId::try_from(field("pid")?)?,
+            cid: p.get("cid").map(String::from),
+        })
+    }
+}
@@ -1,10 +1,85 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fs;
 use std::io::{self, Read};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Instant;
 
 use anyhow::{anyhow, Error, Result};
-use lazy_static::lazy_static;
-use regex::Regex;
+use structopt::StructOpt;
+
+mod convert;
+mod document;
+mod fields;
+mod report;
+mod schema;
+
+use document::{DocumentType, DocumentTypes};
+use fields::TypedPassport;
+use schema::Schema;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    /// Validate part 2 against the rules in this schema file instead of the puzzle's (see
+    /// rules/passport.txt for the format)
+    #[structopt(long, parse(from_os_str))]
+    rules: Option<PathBuf>,
+
+    /// Classify records by the document types in this file instead of the puzzle's (see
+    /// rules/documents.txt for the format)
+    #[structopt(long, parse(from_os_str))]
+    documents: Option<PathBuf>,
+
+    /// Instead of solving, report which fields of each record are missing or invalid and why, as
+    /// "text" or "json"
+    #[structopt(long)]
+    report: Option<report::Format>,
+
+    /// Format of the input: "puzzle" (blank-line-separated key:value pairs), "jsonl" (one JSON
+    /// object per line) or "csv" (with a header row of keys)
+    #[structopt(long, default_value = "puzzle")]
+    from: convert::Format,
+
+    /// What to do with keys other than the eight passport fields: "strict" rejects the record,
+    /// "lenient" ignores the key and "preserve" keeps it as an extra field, which the report lists
+    /// and schemas can have rules for
+    #[structopt(long, default_value = "strict")]
+    unknown_keys: UnknownKeys,
+
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Convert the records to another format, keeping every field
+    Convert {
+        /// "puzzle", "jsonl" or "csv"
+        #[structopt(long)]
+        to: convert::Format,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnknownKeys {
+    Strict,
+    Lenient,
+    Preserve,
+}
+
+impl FromStr for UnknownKeys {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<UnknownKeys> {
+        match s {
+            "strict" => Ok(UnknownKeys::Strict),
+            "lenient" => Ok(UnknownKeys::Lenient),
+            "preserve" => Ok(UnknownKeys::Preserve),
+            _ => Err(anyhow!("Unknown mode for unknown keys: \"{}\"", s)),
+        }
+    }
+}
 
 #[derive(Debug, Default)]
 struct Passport {
@@ -16,140 +91,614 @@ struct Passport {
     ecl: String,
     pid: String,
     cid: Option<String>,
+    /// Any other fields, if they are preserved
+    extras: BTreeMap<String, String>,
 }
 
-impl FromStr for Passport {
-    type Err = Error;
+impl Passport {
+    /// The value of the field with this key, if present.
+    fn get(&self, key: &str) -> Option<&str> {
+        let value = match key {
+            "byr" => &self.byr,
+            "iyr" => &self.iyr,
+            "eyr" => &self.eyr,
+            "hgt" => &self.hgt,
+            "hcl" => &self.hcl,
+            "ecl" => &self.ecl,
+            "pid" => &self.pid,
+            "cid" => return self.cid.as_deref(),
+            _ => return self.extras.get(key).map(String::as_str),
+        };
+        Some(value.as_str()).filter(|v| !v.is_empty())
+    }
 
-    fn from_str(s: &str) -> Result<Passport> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"\b(\w+):(#?\w+)\b").unwrap();
+    /// Set the field with this key. Other keys than the eight known ones are handled according to
+    /// `unknown`.
+    fn set(&mut self, key: &str, value: &str, unknown: UnknownKeys) -> Result<()> {
+        let value = String::from(value);
+        match key {
+            "byr" => self.byr = value,
+            "iyr" => self.iyr = value,
+            "eyr" => self.eyr = value,
+            "hgt" => self.hgt = value,
+            "hcl" => self.hcl = value,
+            "ecl" => self.ecl = value,
+            "pid" => self.pid = value,
+            "cid" => self.cid = Some(value),
+            _ => match unknown {
+                UnknownKeys::Strict => return Err(anyhow!("Unexpected key: {}", key)),
+                UnknownKeys::Lenient => {}
+                UnknownKeys::Preserve => {
+                    self.extras.insert(key.to_string(), value);
+                }
+            },
         }
+        Ok(())
+    }
 
+    fn from_fields(fields: &[(String, String)], unknown: UnknownKeys) -> Result<Passport> {
         let mut result: Passport = Passport::default();
 
-        // Parse
-        for cap in RE.captures_iter(s) {
-            let key = &cap[1];
-            let value = &cap[2];
-            match key {
-                "byr" => result.byr = String::from(value),
-                "iyr" => result.iyr = String::from(value),
-                "eyr" => result.eyr = String::from(value),
-                "hgt" => result.hgt = String::from(value),
-                "hcl" => result.hcl = String::from(value),
-                "ecl" => result.ecl = String::from(value),
-                "pid" => result.pid = String::from(value),
-                "cid" => result.cid = Some(String::from(value)),
-                _ => return Err(anyhow!("Unexpected key: {:#?}", cap)),
-            }
-        }
-
-        // Validate
-        if result.byr == String::default()
-            || result.iyr == String::default()
-            || result.eyr == String::default()
-            || result.hgt == String::default()
-            || result.hcl == String::default()
-            || result.ecl == String::default()
-            || result.pid == String::default()
-        {
-            Err(anyhow!("Invalid Passport: {:#?}", result))
-        } else {
-            Ok(result)
+        for (key, value) in fields {
+            result.set(key, value, unknown)?;
         }
+        Ok(result)
     }
 }
 
-fn part1(input: &str) -> Result<()> {
-    // Blank line separates entries
-    let passports: Vec<Passport> = input.split("\n\n").filter_map(|l| l.parse().ok()).collect();
+/// The whitespace-separated `key:value` pairs of a record, in order.
+fn pairs(s: &str) -> Vec<(String, String)> {
+    s.split_whitespace()
+        .filter_map(|pair| pair.split_once(':'))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
 
-    println!("Part 1: {}", passports.len());
-    Ok(())
+impl FromStr for Passport {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Passport> {
+        Passport::from_fields(&pairs(s), UnknownKeys::Strict)
+    }
 }
 
-fn part2(input: &str) -> Result<()> {
-    // Blank line separates entries
-    let passports: Vec<Passport> = input.split("\n\n").filter_map(|l| l.parse().ok()).collect();
+/// The fields of one passport, and where it came from in the input.
+#[derive(Debug, Clone, PartialEq)]
+struct Record {
+    /// 1-indexed line numbers of the first and last line
+    first_line: usize,
+    last_line: usize,
+    fields: Vec<(String, String)>,
+}
 
-    // Extra validation
-    let valid: Vec<&Passport> = passports
-        .iter()
-        .filter_map(|p| {
-            let byr: u32 = p.byr.parse().unwrap();
-            let iyr: u32 = p.iyr.parse().unwrap();
-            let eyr: u32 = p.eyr.parse().unwrap();
+/// Records in the puzzle's format, which are separated by blank lines.
+fn records(input: &str) -> Vec<Record> {
+    let mut records = vec![];
+    let mut current: Option<Record> = None;
 
-            let mut result = Some(p);
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            records.extend(current.take());
+            continue;
+        }
+        let record = current.get_or_insert_with(|| Record {
+            first_line: i + 1,
+            last_line: i + 1,
+            fields: vec![],
+        });
+        record.last_line = i + 1;
+        record.fields.extend(pairs(line));
+    }
+    records.extend(current);
 
-            if byr < 1920 || byr > 2002 {
-                result = None
-            }
+    records
+}
 
-            if iyr < 2010 || iyr > 2020 {
-                result = None
-            }
+/// A passport that follows the schema and whose fields all parse into their types. The fields
+/// required are those of the schema and, if given, those of the document type.
+fn validate(
+    passport: &Passport,
+    schema: &Schema,
+    document: Option<&DocumentType>,
+) -> Result<TypedPassport> {
+    match document {
+        Some(document) => schema.validate_as(passport, document)?,
+        None => schema.validate(passport)?,
+    }
+    Ok(TypedPassport::try_from(passport)?)
+}
 
-            if eyr < 2020 || eyr > 2030 {
-                result = None
-            }
+/// How many records are of each document type, and how many of those are valid.
+#[derive(Debug, Clone, PartialEq)]
+struct Tally {
+    name: String,
+    count: usize,
+    valid: usize,
+}
 
-            match &p.hgt {
-                s if s.ends_with("cm") => {
-                    let cm: u32 = s[..s.len() - 2].parse().unwrap();
-                    if cm < 150 || cm > 193 {
-                        result = None
-                    }
-                }
-                s if s.ends_with("in") => {
-                    let inch: u32 = s[..s.len() - 2].parse().unwrap();
-                    if inch < 59 || inch > 76 {
-                        result = None
-                    }
-                }
-                _ => result = None,
-            }
+/// Tally the records by document type, in the order the types are defined. Records that can't be
+/// parsed or have no type are counted last, as "unclassified".
+fn tally(
+    records: &[Record],
+    schema: &Schema,
+    types: &DocumentTypes,
+    unknown: UnknownKeys,
+) -> Vec<Tally> {
+    let mut tallies: Vec<Tally> = types
+        .types
+        .iter()
+        .map(|t| t.name.as_str())
+        .chain(Some("unclassified"))
+        .map(|name| Tally {
+            name: name.to_string(),
+            count: 0,
+            valid: 0,
+        })
+        .collect();
 
-            if p.hcl.chars().nth(0).unwrap() != '#' || p.hcl.len() != 7 {
-                result = None
-            } else {
-                for c in p.hcl[1..].chars() {
-                    match c {
-                        '0'..='9' | 'a'..='f' => {}
-                        _ => result = None,
-                    }
-                }
+    for record in records {
+        let passport = Passport::from_fields(&record.fields, unknown).ok();
+        let index = passport
+            .as_ref()
+            .and_then(|p| types.position(p))
+            .unwrap_or(types.types.len());
+        tallies[index].count += 1;
+        if let (Some(document), Some(passport)) = (types.types.get(index), &passport) {
+            if validate(passport, schema, Some(document)).is_ok() {
+                tallies[index].valid += 1;
             }
+        }
+    }
 
-            match p.ecl.as_str() {
-                "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => {}
-                _ => result = None,
-            }
+    tallies
+}
 
-            if p.pid.len() != 9 {
-                result = None
-            }
+fn part1(records: &[Record], types: &DocumentTypes, unknown: UnknownKeys) -> Result<()> {
+    let passports: Vec<Passport> = records
+        .iter()
+        .filter_map(|r| Passport::from_fields(&r.fields, unknown).ok())
+        .filter(|p| types.classify(p).is_some())
+        .collect();
+
+    println!("Part 1: {}", passports.len());
+    Ok(())
+}
 
-            result
+fn part2(
+    records: &[Record],
+    schema: &Schema,
+    types: &DocumentTypes,
+    unknown: UnknownKeys,
+) -> Result<()> {
+    let passports: Vec<(Passport, &DocumentType)> = records
+        .iter()
+        .filter_map(|r| Passport::from_fields(&r.fields, unknown).ok())
+        .filter_map(|p| {
+            let document = types.classify(&p)?;
+            Some((p, document))
         })
         .collect();
 
-    println!("Part 2: {}", valid.len());
+    let valid = passports
+        .iter()
+        .filter(|(p, document)| validate(p, schema, Some(document)).is_ok())
+        .count();
+
+    println!("Part 2: {}", valid);
     Ok(())
 }
 
 fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let schema = match &opt.rules {
+        Some(path) => fs::read_to_string(path)?.parse()?,
+        None => Schema::default(),
+    };
+    let types = match &opt.documents {
+        Some(path) => fs::read_to_string(path)?.parse()?,
+        None => DocumentTypes::default(),
+    };
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
 
+    let records = convert::read(&input, opt.from)?;
+
+    if let Some(Command::Convert { to }) = opt.cmd {
+        return convert::write(&mut io::stdout().lock(), &records, to);
+    }
+
+    if let Some(format) = opt.report {
+        let entries = report::report(&records, &schema, &types, opt.unknown_keys);
+        return report::write(&mut io::stdout().lock(), &entries, format);
+    }
+
     let now = Instant::now();
-    part1(&input)?;
+    part1(&records, &types, opt.unknown_keys)?;
     println!("Part 1 took: {:#?}", now.elapsed());
 
     let now = Instant::now();
-    part2(&input)?;
+    part2(&records, &schema, &types, opt.unknown_keys)?;
     println!("Part 2 took: {:#?}", now.elapsed());
 
+    for t in tally(&records, &schema, &types, opt.unknown_keys) {
+        if t.name == "unclassified" {
+            println!("{}: {}", t.name, t.count);
+        } else {
+            println!("{}: {} ({} valid)", t.name, t.count, t.valid);
+        }
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema() -> Result<()> {
+        let schema = Schema::default();
+        let check = |field: &str, value: &str| {
+            schema
+                .rules
+                .iter()
+                .find(|r| r.field == field)
+                .unwrap()
+                .check(value)
+        };
+
+        assert_eq!(Ok(()), check("byr", "2002"));
+        assert!(check("byr", "2003").is_err());
+        assert_eq!(Ok(()), check("hgt", "60in"));
+        assert_eq!(Ok(()), check("hgt", "190cm"));
+        assert!(check("hgt", "190in").is_err());
+        assert!(check("hgt", "190").is_err());
+        assert_eq!(Ok(()), check("hcl", "#123abc"));
+        assert!(check("hcl", "#123abz").is_err());
+        assert!(check("hcl", "123abc").is_err());
+        assert!(check("ecl", "wat").is_err());
+        assert_eq!(Ok(()), check("pid", "000000001"));
+        assert!(check("pid", "0123456789").is_err());
+
+        let valid: Passport =
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f".parse()?;
+        assert!(schema.validate(&valid).is_ok());
+        let invalid: Passport =
+            "eyr:1972 cid:100\nhcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926".parse()?;
+        assert!(schema.validate(&invalid).is_err());
+
+        // Policies can change without a recompile
+        let schema: Schema =
+            "byr required number range=1900-1999\nhgt optional number units=in:1-99".parse()?;
+        assert!(schema.validate(&valid).is_ok());
+        assert!("byr required year".parse::<Schema>().is_err());
+        assert!("ecl text".parse::<Schema>().is_err());
+        assert!("byr required text range=1-2".parse::<Schema>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed() -> Result<()> {
+        use fields::{EyeColor, FieldError, HairColor, Height, Year};
+
+        let passport: Passport =
+            "hcl:#623a2f pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980".parse()?;
+        let typed = validate(&passport, &Schema::default(), None)?;
+        assert_eq!(Year(1980), typed.byr);
+        assert_eq!(Height::In(74), typed.hgt);
+        assert_eq!(
+            HairColor {
+                r: 0x62,
+                g: 0x3a,
+                b: 0x2f
+            },
+            typed.hcl
+        );
+        assert_eq!(EyeColor::Green, typed.ecl);
+        assert_eq!("087499704", typed.pid.0);
+
+        // Bad values are errors, not panics
+        assert_eq!(
+            Err(FieldError::Year("abc".to_string())),
+            Year::try_from("abc")
+        );
+        assert!(Year::try_from("+123").is_err());
+        assert!(Height::try_from("cm").is_err());
+        assert!(Height::try_from("é").is_err());
+        assert!(HairColor::try_from("").is_err());
+        assert!(HairColor::try_from("#12345é").is_err());
+        let passport = Passport {
+            byr: "abc".to_string(),
+            hgt: "cm".to_string(),
+            ..Passport::default()
+        };
+        assert_eq!(
+            Err(FieldError::Year("abc".to_string())),
+            TypedPassport::try_from(&passport)
+        );
+        assert!(validate(&passport, &Schema::default(), None).is_err());
+
+        // A schema can't make typed fields optional or allow other eye colors
+        let schema: Schema = "hgt optional number units=cm:150-193,in:59-76\n\
+                              ecl required text enum=amb,vio"
+            .parse()?;
+        let passport: Passport =
+            "hcl:#623a2f pid:087499704 ecl:vio iyr:2012 eyr:2030 byr:1980".parse()?;
+        assert!(schema.validate(&passport).is_ok());
+        assert_eq!(
+            Err(FieldError::Missing("hgt")),
+            TypedPassport::try_from(&passport)
+        );
+        let passport = Passport {
+            hgt: "74in".to_string(),
+            ..passport
+        };
+        assert_eq!(
+            Err(FieldError::EyeColor("vio".to_string())),
+            TypedPassport::try_from(&passport)
+        );
+        assert!(validate(&passport, &schema, None).is_err());
+
+        let types: DocumentTypes = "permit: byr ecl".parse()?;
+        let entries = report::report(
+            &records("byr:1980 ecl:vio"),
+            &schema,
+            &types,
+            UnknownKeys::Strict,
+        );
+        assert!(!entries[0].valid);
+        assert_eq!(vec!["iyr".to_string()], entries[0].missing);
+        assert_eq!("ecl", entries[0].invalid[0].field);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report() -> Result<()> {
+        let input = "\nbyr:2010 pid:#1bb4d8 eyr:2021 hgt:186cm iyr:2020 ecl:grt\n\n\n\
+                     pid:937877382 eyr:2029\necl:amb hgt:187cm iyr:2019\nbyr:1933 hcl:#888785\n\n\
+                     hgt:59 hcl:#888785 pid:937877382 eyr:2029 ecl:amb iyr:2019 byr:1933 xyz:1\n";
+        let entries = report::report(
+            &records(input),
+            &Schema::default(),
+            &DocumentTypes::default(),
+            UnknownKeys::Strict,
+        );
+
+        assert_eq!(3, entries.len());
+        assert_eq!((2, 2), (entries[0].first_line, entries[0].last_line));
+        assert_eq!(vec!["hcl".to_string()], entries[0].missing);
+        assert_eq!(
+            vec!["byr", "ecl", "pid"],
+            entries[0]
+                .invalid
+                .iter()
+                .map(|f| f.field.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert!(entries[1].valid);
+        assert_eq!((5, 7), (entries[1].first_line, entries[1].last_line));
+        assert_eq!(
+            vec![
+                ("xyz", "Unexpected key: xyz"),
+                ("hgt", "\"59\" does not have a valid unit")
+            ],
+            entries[2]
+                .invalid
+                .iter()
+                .map(|f| (f.field.as_str(), f.reason.as_str()))
+                .collect::<Vec<_>>()
+        );
+
+        let mut json = vec![];
+        report::write(&mut json, &entries[1..2], report::Format::Json)?;
+        let json: serde_json::Value = serde_json::from_slice(&json)?;
+        assert_eq!(2, json[0]["index"]);
+        assert_eq!(true, json[0]["valid"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert() -> Result<()> {
+        use convert::Format;
+
+        let input = "ecl:gry pid:860033327\ncid:147 note:a,\"b\"\n\nhcl:#ae17e1 iyr:2013\n";
+        let records = convert::read(input, Format::Puzzle)?;
+
+        let mut csv = vec![];
+        convert::write(&mut csv, &records, Format::Csv)?;
+        let csv = String::from_utf8(csv)?;
+        assert_eq!(
+            "ecl,pid,cid,note,hcl,iyr\ngry,860033327,147,\"a,\"\"b\"\"\",,\n,,,,#ae17e1,2013\n",
+            csv
+        );
+
+        let mut jsonl = vec![];
+        convert::write(&mut jsonl, &records, Format::Jsonl)?;
+        let jsonl = String::from_utf8(jsonl)?;
+        assert_eq!(
+            "{\"ecl\":\"gry\",\"pid\":\"860033327\",\"cid\":\"147\",\"note\":\"a,\\\"b\\\"\"}\n",
+            jsonl.lines().next().unwrap().to_string() + "\n"
+        );
+
+        for (text, format) in &[(csv, Format::Csv), (jsonl, Format::Jsonl)] {
+            let read = convert::read(text, *format)?;
+            assert_eq!(
+                records.iter().map(|r| &r.fields).collect::<Vec<_>>(),
+                read.iter().map(|r| &r.fields).collect::<Vec<_>>()
+            );
+        }
+        let multiline = convert::read("pid,note\n1,\"two\nlines\"\n2,x\n", Format::Csv)?;
+        assert_eq!((2, 3), (multiline[0].first_line, multiline[0].last_line));
+        assert_eq!(4, multiline[1].first_line);
+        assert!(convert::read("pid,cid\n1\n", Format::Csv).is_err());
+        assert!(convert::read("[1, 2]\n", Format::Jsonl).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_round_trip() -> Result<()> {
+        use convert::Format;
+
+        let record = |fields: &[(&str, &str)]| Record {
+            first_line: 1,
+            last_line: 1,
+            fields: fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        };
+        let round_trip = |record: &Record, format: Format| -> Result<Vec<(String, String)>> {
+            let mut out = vec![];
+            convert::write(&mut out, std::slice::from_ref(record), format)?;
+            let read = convert::read(&String::from_utf8(out)?, format)?;
+            Ok(read.into_iter().flat_map(|r| r.fields).collect())
+        };
+
+        // Each format rejects the records it can't represent, and round-trips the rest
+        let spaced = record(&[("note", "two words"), ("pid", "1")]);
+        let colon = record(&[("a:b", "1")]);
+        let empty = record(&[("hcl", ""), ("pid", "1")]);
+        let duplicate = record(&[("pid", "1"), ("pid", "2")]);
+        let cases = [
+            (&spaced, Format::Puzzle, false),
+            (&spaced, Format::Jsonl, true),
+            (&spaced, Format::Csv, true),
+            (&colon, Format::Puzzle, false),
+            (&colon, Format::Jsonl, true),
+            (&colon, Format::Csv, true),
+            (&empty, Format::Puzzle, true),
+            (&empty, Format::Jsonl, true),
+            (&empty, Format::Csv, false),
+            (&duplicate, Format::Puzzle, true),
+            (&duplicate, Format::Jsonl, false),
+            (&duplicate, Format::Csv, false),
+            (&record(&[]), Format::Puzzle, false),
+        ];
+        for (record, format, ok) in cases.iter() {
+            match round_trip(record, *format) {
+                Ok(fields) => {
+                    assert!(ok, "{:?} as {:?}", record.fields, format);
+                    assert_eq!(record.fields, fields, "{:?}", format);
+                }
+                Err(_) => assert!(!ok, "{:?} as {:?}", record.fields, format),
+            }
+        }
+
+        // Nothing is written if any record fails
+        let mut out = vec![];
+        assert!(
+            convert::write(&mut out, &[record(&[("pid", "1")]), spaced], Format::Puzzle).is_err()
+        );
+        assert!(out.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_keys() -> Result<()> {
+        let input =
+            "hcl:#623a2f pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 seat:12A";
+        let fields = pairs(input);
+
+        assert!(Passport::from_fields(&fields, UnknownKeys::Strict).is_err());
+        let lenient = Passport::from_fields(&fields, UnknownKeys::Lenient)?;
+        assert!(lenient.extras.is_empty());
+        let preserved = Passport::from_fields(&fields, UnknownKeys::Preserve)?;
+        assert_eq!(Some("12A"), preserved.get("seat"));
+        assert!(validate(&preserved, &Schema::default(), None).is_ok());
+
+        // Preserved fields can have rules of their own
+        let schema: Schema = "seat required text regex=[0-9]+[A-F]".parse()?;
+        assert!(schema.validate(&preserved).is_ok());
+        assert!(schema.validate(&lenient).is_err());
+
+        let records = records(input);
+        let schema = Schema::default();
+        let types = DocumentTypes::default();
+        let entries = report::report(&records, &schema, &types, UnknownKeys::Strict);
+        assert_eq!("seat", entries[0].invalid[0].field);
+        let entries = report::report(&records, &schema, &types, UnknownKeys::Lenient);
+        assert!(entries[0].valid && entries[0].extras.is_empty());
+        let entries = report::report(&records, &schema, &types, UnknownKeys::Preserve);
+        assert!(entries[0].valid);
+        assert_eq!(Some(&"12A".to_string()), entries[0].extras.get("seat"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_document_types() -> Result<()> {
+        let input =
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm\n\n\
+                     iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884\nhcl:#cfa07d byr:1929\n\n\
+                     hcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm\n\n\
+                     hcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in\n";
+        let records = records(input);
+        let tallies = tally(
+            &records,
+            &Schema::default(),
+            &DocumentTypes::default(),
+            UnknownKeys::Strict,
+        );
+        let counts: Vec<_> = tallies
+            .iter()
+            .map(|t| (t.name.as_str(), t.count, t.valid))
+            .collect();
+        assert_eq!(
+            vec![
+                ("passport", 1, 1),
+                ("north-pole-credential", 1, 1),
+                ("unclassified", 2, 0)
+            ],
+            counts
+        );
+
+        // A document type that doesn't need a birth year
+        let types: DocumentTypes = "visa: iyr eyr hgt hcl ecl pid".parse()?;
+        let tallies = tally(&records, &Schema::default(), &types, UnknownKeys::Strict);
+        assert_eq!((3, 1), (tallies[0].count, tallies[1].count));
+
+        // A visa without a birth year is still not valid, since the schema requires one
+        assert_eq!(2, tallies[0].valid);
+        let entries = report::report(&records, &Schema::default(), &types, UnknownKeys::Strict);
+        assert_eq!(Some("visa"), entries[3].document.as_deref());
+        assert!(!entries[3].valid);
+        assert_eq!(vec!["byr".to_string()], entries[3].missing);
+        assert_eq!(vec!["hgt".to_string()], entries[1].missing);
+        let visa = &types.types[0];
+        let passport = Passport::from_fields(&records[3].fields, UnknownKeys::Strict)?;
+        assert!(validate(&passport, &Schema::default(), Some(visa)).is_err());
+
+        // The schema can require fields that the document type doesn't
+        let schema: Schema = include_str!("../rules/passport.txt")
+            .replace("cid optional", "cid required")
+            .parse()?;
+        let tallies = tally(
+            &records,
+            &schema,
+            &DocumentTypes::default(),
+            UnknownKeys::Strict,
+        );
+        assert_eq!((1, 0), (tallies[1].count, tallies[1].valid));
+        let entries = report::report(
+            &records,
+            &schema,
+            &DocumentTypes::default(),
+            UnknownKeys::Strict,
+        );
+        assert_eq!(vec!["cid".to_string()], entries[2].missing);
+        let credential = &DocumentTypes::default().types[1];
+        let passport = Passport::from_fields(&records[2].fields, UnknownKeys::Strict)?;
+        assert!(validate(&passport, &Schema::default(), Some(credential)).is_ok());
+        assert!(validate(&passport, &schema, Some(credential)).is_err());
+        assert!("visa byr".parse::<DocumentTypes>().is_err());
+        assert!("a: byr\na: iyr".parse::<DocumentTypes>().is_err());
+
+        Ok(())
+    }
+}
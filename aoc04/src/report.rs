@@ -0,0 +1,174 @@
+//! A validation report of every record: which required fields it is missing, and which fields
+//! fail validation and why.
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+use serde::Serialize;
+
+use crate::document::DocumentTypes;
+use crate::fields::{self, FieldError, TypedPassport};
+use crate::schema::Schema;
+use crate::{Passport, Record, UnknownKeys};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Format> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(anyhow!("Unknown report format: \"{}\"", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Failure {
+    pub field: String,
+    pub value: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Entry {
+    /// 1-indexed position of the record in the input
+    pub index: usize,
+    /// 1-indexed line numbers of the first and last line of the record
+    pub first_line: usize,
+    pub last_line: usize,
+    /// The name of the record's document type, if it has one
+    pub document: Option<String>,
+    pub valid: bool,
+    /// The fields required by the record's document type, or the closest type if it has none,
+    /// that it doesn't have
+    pub missing: Vec<String>,
+    pub invalid: Vec<Failure>,
+    /// Fields other than the eight passport fields, if they are preserved
+    pub extras: BTreeMap<String, String>,
+}
+
+/// Check every record against the schema and the field types, collecting every problem rather
+/// than stopping at the first.
+pub fn report(
+    records: &[Record],
+    schema: &Schema,
+    types: &DocumentTypes,
+    unknown: UnknownKeys,
+) -> Vec<Entry> {
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let mut passport = Passport::default();
+            let mut invalid = vec![];
+            let failure = |field: &str, value: &str, reason: String| Failure {
+                field: field.to_string(),
+                value: value.to_string(),
+                reason,
+            };
+
+            for (key, value) in &record.fields {
+                if let Err(e) = passport.set(key, value, unknown) {
+                    invalid.push(failure(key, value, e.to_string()));
+                }
+            }
+
+            let mut missing = types.closest(&passport).map_or_else(
+                || schema.missing(&passport),
+                |t| schema.missing_as(&passport, t),
+            );
+            for (field, reason) in schema.invalid(&passport) {
+                let value = passport.get(&field).unwrap_or_default();
+                invalid.push(failure(&field, value, reason));
+            }
+            // Fields the schema accepts must still parse into their types
+            for (key, _) in &record.fields {
+                let value = match passport.get(key) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                if invalid.iter().all(|f| f.field != *key) {
+                    if let Err(e) = fields::check(key, value) {
+                        invalid.push(failure(key, value, e.to_string()));
+                    }
+                }
+            }
+
+            let typed = TypedPassport::try_from(&passport);
+            if let Err(FieldError::Missing(key)) = typed {
+                if !missing.iter().any(|m| m == key) {
+                    missing.push(key.to_string());
+                }
+            }
+
+            let document = types.classify(&passport).map(|t| t.name.clone());
+
+            Entry {
+                index: i + 1,
+                first_line: record.first_line,
+                last_line: record.last_line,
+                valid: missing.is_empty()
+                    && invalid.is_empty()
+                    && typed.is_ok()
+                    && document.is_some(),
+                document,
+                missing,
+                invalid,
+                extras: passport.extras,
+            }
+        })
+        .collect()
+}
+
+pub fn write<W: Write>(out: &mut W, entries: &[Entry], format: Format) -> Result<()> {
+    match format {
+        Format::Text => {
+            for entry in entries {
+                let mut problems = vec![];
+                if !entry.missing.is_empty() {
+                    problems.push(format!("missing {}", entry.missing.join(", ")));
+                }
+                for f in &entry.invalid {
+                    problems.push(format!("invalid {} \"{}\": {}", f.field, f.value, f.reason));
+                }
+                let extras: Vec<String> = entry
+                    .extras
+                    .iter()
+                    .map(|(key, value)| format!("{}:{}", key, value))
+                    .collect();
+                write!(
+                    out,
+                    "Record {} (lines {}-{}, {}): {}",
+                    entry.index,
+                    entry.first_line,
+                    entry.last_line,
+                    entry.document.as_deref().unwrap_or("unclassified"),
+                    if entry.valid {
+                        "valid".to_string()
+                    } else {
+                        problems.join("; ")
+                    }
+                )?;
+                if !extras.is_empty() {
+                    write!(out, " (extra fields {})", extras.join(" "))?;
+                }
+                writeln!(out)?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, entries)?;
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
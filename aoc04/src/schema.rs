@@ -0,0 +1,253 @@
+//! Passport validation rules, declared in a data file rather than in code.
+//!
+//! A schema has one line per field: its name, `required` or `optional`, its type (`number` or
+//! `text`) and any constraints, e.g.
+//!
+//! ```text
+//! byr required number range=1920-2002
+//! hgt required number units=cm:150-193,in:59-76
+//! hcl required text regex=#[0-9a-f]{6}
+//! ecl required text enum=amb,blu,brn,gry,grn,hzl,oth
+//! cid optional text
+//! ```
+//!
+//! Lines starting with `//` are comments. The rules of part 2 are in `rules/passport.txt`.
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+use regex::Regex;
+
+use crate::document::DocumentType;
+use crate::Passport;
+
+/// The schema used when no other is given.
+const DEFAULT: &str = include_str!("../rules/passport.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type {
+    /// A whole number, followed by a unit if the rule lists any
+    Number,
+    Text,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub field: String,
+    pub required: bool,
+    pub kind: Type,
+    pub range: Option<(u64, u64)>,
+    pub units: Vec<(String, (u64, u64))>,
+    pub regex: Option<Regex>,
+    pub values: Vec<String>,
+}
+
+// Parse "MIN-MAX"
+fn parse_range(s: &str) -> Result<(u64, u64)> {
+    let mut bounds = s.splitn(2, '-');
+    match (bounds.next(), bounds.next()) {
+        (Some(min), Some(max)) => Ok((min.parse()?, max.parse()?)),
+        _ => Err(anyhow!("Invalid range: \"{}\"", s)),
+    }
+}
+
+fn check_range(n: u64, (min, max): (u64, u64)) -> Result<(), String> {
+    if (min..=max).contains(&n) {
+        Ok(())
+    } else {
+        Err(format!("{} is not between {} and {}", n, min, max))
+    }
+}
+
+impl Rule {
+    /// Check a value of this rule's field against its type and constraints.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        if self.kind == Type::Number {
+            let digits = value
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(value.len());
+            let (number, unit) = value.split_at(digits);
+            let n: u64 = number
+                .parse()
+                .map_err(|_| format!("\"{}\" is not a number", value))?;
+
+            if self.units.is_empty() {
+                if !unit.is_empty() {
+                    return Err(format!("\"{}\" is not a number", value));
+                }
+            } else {
+                let range = self
+                    .units
+                    .iter()
+                    .find(|(u, _)| u == unit)
+                    .map(|(_, range)| *range)
+                    .ok_or_else(|| format!("\"{}\" does not have a valid unit", value))?;
+                check_range(n, range).map_err(|e| format!("{}{}", e, unit))?;
+            }
+            if let Some(range) = self.range {
+                check_range(n, range)?;
+            }
+        }
+
+        if let Some(re) = &self.regex {
+            if !re.is_match(value) {
+                return Err(format!("\"{}\" does not match {}", value, re));
+            }
+        }
+        if !self.values.is_empty() && !self.values.iter().any(|v| v == value) {
+            return Err(format!(
+                "\"{}\" is not one of {}",
+                value,
+                self.values.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Rule> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let (field, required, kind, constraints) = match fields.as_slice() {
+            [field, required, kind, constraints @ ..] => (field, required, kind, constraints),
+            _ => return Err(anyhow!("Invalid rule: \"{}\"", s)),
+        };
+
+        let mut rule = Rule {
+            field: field.to_string(),
+            required: match *required {
+                "required" => true,
+                "optional" => false,
+                _ => return Err(anyhow!("Expected required or optional: \"{}\"", s)),
+            },
+            kind: match *kind {
+                "number" => Type::Number,
+                "text" => Type::Text,
+                _ => return Err(anyhow!("Unknown type \"{}\" in rule: \"{}\"", kind, s)),
+            },
+            range: None,
+            units: vec![],
+            regex: None,
+            values: vec![],
+        };
+
+        for constraint in constraints {
+            let mut kv = constraint.splitn(2, '=');
+            match (kv.next(), kv.next()) {
+                (Some("range"), Some(range)) => rule.range = Some(parse_range(range)?),
+                (Some("units"), Some(units)) => {
+                    for unit in units.split(',') {
+                        let mut parts = unit.splitn(2, ':');
+                        match (parts.next(), parts.next()) {
+                            (Some(name), Some(range)) if !name.is_empty() => {
+                                rule.units.push((name.to_string(), parse_range(range)?))
+                            }
+                            _ => return Err(anyhow!("Invalid unit: \"{}\"", unit)),
+                        }
+                    }
+                }
+                (Some("regex"), Some(pattern)) => {
+                    rule.regex = Some(Regex::new(&format!("^(?:{})$", pattern))?)
+                }
+                (Some("enum"), Some(values)) => {
+                    rule.values = values.split(',').map(String::from).collect()
+                }
+                _ => return Err(anyhow!("Invalid constraint: \"{}\"", constraint)),
+            }
+        }
+        if rule.kind == Type::Text && (rule.range.is_some() || !rule.units.is_empty()) {
+            return Err(anyhow!("Only numbers can have a range or units: \"{}\"", s));
+        }
+
+        Ok(rule)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub rules: Vec<Rule>,
+}
+
+impl Schema {
+    /// The required fields that the passport doesn't have.
+    pub fn missing(&self, passport: &Passport) -> Vec<String> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.required && passport.get(&rule.field).is_none())
+            .map(|rule| rule.field.clone())
+            .collect()
+    }
+
+    /// The fields of the passport that don't follow their rule, with the reason why.
+    pub fn invalid(&self, passport: &Passport) -> Vec<(String, String)> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let value = passport.get(&rule.field)?;
+                let reason = rule.check(value).err()?;
+                Some((rule.field.clone(), reason))
+            })
+            .collect()
+    }
+
+    /// Check that every required field is present, and that every field present follows its rule.
+    pub fn validate(&self, passport: &Passport) -> Result<()> {
+        first_problem(self.missing(passport), self.invalid(passport))
+    }
+
+    /// The fields that the schema or the document type requires and the passport doesn't have,
+    /// those of the schema first.
+    pub fn missing_as(&self, passport: &Passport, document: &DocumentType) -> Vec<String> {
+        let mut missing = self.missing(passport);
+        for field in document.missing(passport) {
+            if !missing.contains(&field) {
+                missing.push(field);
+            }
+        }
+        missing
+    }
+
+    /// Check that every field the schema or the document type requires is present, and that every
+    /// field present follows its rule.
+    pub fn validate_as(&self, passport: &Passport, document: &DocumentType) -> Result<()> {
+        first_problem(self.missing_as(passport, document), self.invalid(passport))
+    }
+}
+
+fn first_problem(missing: Vec<String>, invalid: Vec<(String, String)>) -> Result<()> {
+    if let Some(field) = missing.first() {
+        return Err(anyhow!("Missing required field {}", field));
+    }
+    if let Some((field, reason)) = invalid.first() {
+        return Err(anyhow!("Invalid {}: {}", field, reason));
+    }
+    Ok(())
+}
+
+impl Default for Schema {
+    fn default() -> Schema {
+        DEFAULT.parse().expect("the default schema is valid")
+    }
+}
+
+impl FromStr for Schema {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Schema> {
+        let mut rules: Vec<Rule> = vec![];
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let rule: Rule = line.parse()?;
+            if rules.iter().any(|r| r.field == rule.field) {
+                return Err(anyhow!("Field {} is in the schema twice", rule.field));
+            }
+            rules.push(rule);
+        }
+
+        Ok(Schema { rules })
+    }
+}
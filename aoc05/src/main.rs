@@ -3,6 +3,30 @@ use std::str::FromStr;
 use std::time::Instant;
 
 use anyhow::{anyhow, Error, Result};
+use structopt::StructOpt;
+
+/// Rows and columns of seats on the plane
+const ROWS: u32 = 128;
+const COLS: u32 = 8;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Print the boarding pass of a seat, given either its row and column or its seat ID
+    Encode {
+        #[structopt(long, requires = "col", conflicts_with = "id")]
+        row: Option<u32>,
+        #[structopt(long, requires = "row")]
+        col: Option<u32>,
+        #[structopt(long, required_unless = "row")]
+        id: Option<u32>,
+    },
+}
 
 #[derive(Debug)]
 struct Seat {
@@ -53,6 +77,30 @@ fn decode(input: &str) -> Result<(u32, u32, u32)> {
     Ok((row, col, row * MULTIPLIER + col))
 }
 
+/// The boarding pass of the seat in `row` and `col`, the inverse of `decode`.
+fn encode(row: u32, col: u32) -> Result<String> {
+    if row >= ROWS || col >= COLS {
+        return Err(anyhow!("No seat at row {}, column {}", row, col));
+    }
+
+    // Each letter halves the range, so the pass is the row and column in binary
+    let row = (0..7)
+        .rev()
+        .map(|bit| if row >> bit & 1 == 1 { 'B' } else { 'F' });
+    let col = (0..3)
+        .rev()
+        .map(|bit| if col >> bit & 1 == 1 { 'R' } else { 'L' });
+    Ok(row.chain(col).collect())
+}
+
+/// The boarding pass of the seat with this ID.
+fn encode_id(id: u32) -> Result<String> {
+    if id >= ROWS * COLS {
+        return Err(anyhow!("No seat has ID {}", id));
+    }
+    encode(id / COLS, id % COLS)
+}
+
 fn part1(input: &str) -> Result<()> {
     let seats: Vec<Seat> = input.lines().filter_map(|l| l.parse().ok()).collect();
 
@@ -65,7 +113,7 @@ fn part1(input: &str) -> Result<()> {
 fn part2(input: &str) -> Result<()> {
     let mut seats: Vec<Seat> = input.lines().filter_map(|l| l.parse().ok()).collect();
 
-    seats.sort_by(|a, b| a.id.cmp(&b.id));
+    seats.sort_by_key(|s| s.id);
 
     let mut next = seats[0].id + 1;
     for seat in &seats[1..] {
@@ -74,13 +122,28 @@ fn part2(input: &str) -> Result<()> {
             println!("Part 2: {}", next);
             break;
         }
-        next += 1;
+        next = seat.id + 1;
     }
 
     Ok(())
 }
 
 fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    if let Some(Command::Encode { row, col, id }) = opt.cmd {
+        let pass = match (row, col, id) {
+            (Some(row), Some(col), _) => encode(row, col)?,
+            (_, _, Some(id)) => encode_id(id)?,
+            _ => return Err(anyhow!("Give either --row and --col, or --id")),
+        };
+        let seat: Seat = pass.parse()?;
+        println!(
+            "{} (row {}, column {}, seat ID {})",
+            pass, seat.row, seat.col, seat.id
+        );
+        return Ok(());
+    }
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
 
@@ -107,4 +170,41 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> Result<()> {
+        assert_eq!("BFFFBBFRRR", encode(70, 7)?);
+        assert_eq!("BBFFBBFRLL", encode_id(820)?);
+        assert!(encode(128, 0).is_err());
+        assert!(encode(0, 8).is_err());
+        assert!(encode_id(1024).is_err());
+
+        // Every seat on the plane round-trips through its pass
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                let id = row * COLS + col;
+                let pass = encode(row, col)?;
+                assert_eq!((row, col, id), decode(&pass)?);
+                assert_eq!(pass, encode_id(id)?);
+            }
+        }
+
+        // And every pass round-trips through its seat
+        for bits in 0..1 << 10 {
+            let pass: String = (0..10)
+                .rev()
+                .map(|bit| match (bit >= 3, bits >> bit & 1 == 1) {
+                    (true, false) => 'F',
+                    (true, true) => 'B',
+                    (false, false) => 'L',
+                    (false, true) => 'R',
+                })
+                .collect();
+            let (row, col, id) = decode(&pass)?;
+            assert_eq!(pass, encode(row, col)?);
+            assert_eq!(pass, encode_id(id)?);
+        }
+
+        Ok(())
+    }
 }
@@ -3,6 +3,24 @@ use std::io::{self, Read};
 use std::time::Instant;
 
 use anyhow::{anyhow, Result};
+use crosscheck::{file_cases, generated_cases, Case, Command, Rng, Strategy, VerifyOpt};
+use structopt::StructOpt;
+
+const EXAMPLE: &str = "nop +0\n\
+                       acc +1\n\
+                       jmp +4\n\
+                       acc +3\n\
+                       jmp -3\n\
+                       acc -99\n\
+                       acc +1\n\
+                       jmp -4\n\
+                       acc +6";
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Instruction {
@@ -26,7 +44,7 @@ fn parse_input(input: &str) -> Result<Vec<Instruction>> {
     Ok(instructions)
 }
 
-fn part1(instructions: &Vec<Instruction>) -> Result<i32> {
+fn part1(instructions: &[Instruction]) -> Result<i32> {
     let mut accumulator = 0;
     let mut visited: HashSet<i32> = HashSet::new();
     let mut pc: i32 = 0;
@@ -55,35 +73,147 @@ fn part1(instructions: &Vec<Instruction>) -> Result<i32> {
 
 // Naive brute force attempt. Literally swap every nop to jmp and jmp to nop until we find the
 // solution.
-fn part2(instructions: &mut Vec<Instruction>) -> Result<()> {
+fn part2(instructions: &[Instruction]) -> Result<i32> {
+    let mut instructions = instructions.to_vec();
     let len = instructions.len();
     for i in 0..len {
         let instruction = instructions[i];
-        let tmp = match instruction {
-            Instruction::Jmp(n) => Instruction::Nop(n),
-            Instruction::Nop(n) => Instruction::Jmp(n),
-            Instruction::Acc(_) => continue,
+        let tmp = match flip(instruction) {
+            Some(tmp) => tmp,
+            None => continue,
         };
 
         // Swap in the temporary change
         instructions[i] = tmp;
 
         match part1(&instructions) {
-            Ok(n) => {
-                println!("Part 2: {}", n);
-                break;
-            }
+            Ok(n) => return Ok(n),
             Err(_) => instructions[i] = instruction,
         }
     }
 
-    Ok(())
+    Err(anyhow!("No solution found."))
+}
+
+// Swap a jmp for a nop or vice versa
+fn flip(instruction: Instruction) -> Option<Instruction> {
+    match instruction {
+        Instruction::Jmp(n) => Some(Instruction::Nop(n)),
+        Instruction::Nop(n) => Some(Instruction::Jmp(n)),
+        Instruction::Acc(_) => None,
+    }
+}
+
+fn next_pc(pc: i32, instruction: Instruction) -> i32 {
+    match instruction {
+        Instruction::Jmp(n) => pc + n,
+        _ => pc + 1,
+    }
+}
+
+// Work out which instructions lead out of the program, then only try flipping the instructions
+// on the original path whose flipped version jumps to one of them.
+fn part2_reachability(instructions: &[Instruction]) -> Result<i32> {
+    let len = instructions.len() as i32;
+    let in_program = |pc: i32| pc >= 0 && pc < len;
+
+    // exits[pc] is Some(true) if running from pc leaves the program, Some(false) if it loops
+    let mut exits: Vec<Option<bool>> = vec![None; instructions.len()];
+    for start in 0..len {
+        let mut path = vec![];
+        let mut pc = start;
+        let result = loop {
+            if !in_program(pc) {
+                break true;
+            }
+            if let Some(known) = exits[pc as usize] {
+                break known;
+            }
+            if path.contains(&pc) {
+                break false;
+            }
+            path.push(pc);
+            pc = next_pc(pc, instructions[pc as usize]);
+        };
+        for pc in path {
+            exits[pc as usize] = Some(result);
+        }
+    }
+
+    let mut visited: HashSet<i32> = HashSet::new();
+    let mut pc = 0;
+    while in_program(pc) && visited.insert(pc) {
+        if let Some(flipped) = flip(instructions[pc as usize]) {
+            let target = next_pc(pc, flipped);
+            if !in_program(target) || exits[target as usize] == Some(true) {
+                let mut repaired = instructions.to_vec();
+                repaired[pc as usize] = flipped;
+                // The flipped instruction may itself be on the new path, so double check
+                if let Ok(n) = part1(&repaired) {
+                    return Ok(n);
+                }
+            }
+        }
+        pc = next_pc(pc, instructions[pc as usize]);
+    }
+
+    Err(anyhow!("No solution found."))
+}
+
+fn part2_strategies() -> Vec<Strategy<Vec<Instruction>, i32>> {
+    vec![
+        Strategy::new("brute force", |i| part2(i)),
+        Strategy::new("reachability", |i| part2_reachability(i)),
+    ]
+}
+
+// A random program which loops, but terminates after exactly one jmp/nop is flipped
+fn generate(rng: &mut Rng) -> Vec<Instruction> {
+    loop {
+        let len = rng.range(5, 40);
+        let program: Vec<Instruction> = (0..len)
+            .map(|_| match rng.range(0, 3) {
+                0 => Instruction::Nop(rng.range(-5, 6) as i32),
+                1 => Instruction::Acc(rng.range(-20, 21) as i32),
+                _ => Instruction::Jmp(rng.range(-5, 6) as i32),
+            })
+            .collect();
+
+        if part1(&program).is_ok() {
+            continue;
+        }
+        let fixes = (0..program.len())
+            .filter(|i| match flip(program[*i]) {
+                Some(flipped) => {
+                    let mut repaired = program.clone();
+                    repaired[*i] = flipped;
+                    part1(&repaired).is_ok()
+                }
+                None => false,
+            })
+            .count();
+        if fixes == 1 {
+            return program;
+        }
+    }
+}
+
+fn verify(opt: &VerifyOpt) -> Result<()> {
+    let mut cases = vec![Case::new("example", parse_input(EXAMPLE)?)];
+    cases.extend(file_cases(opt, parse_input)?);
+    cases.extend(generated_cases(opt, generate));
+
+    crosscheck::run("Part 2", &part2_strategies(), &cases)
 }
 
 fn main() -> Result<()> {
+    if let Some(Command::Verify(opt)) = Opt::from_args().cmd {
+        return verify(&opt);
+    }
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
-    let mut instructions = parse_input(&input)?;
+    let instructions = parse_input(&input)?;
 
     let now = Instant::now();
     if let Err(e) = part1(&instructions) {
@@ -92,7 +222,7 @@ fn main() -> Result<()> {
     println!("Part 1 took: {:#?}", now.elapsed());
 
     let now = Instant::now();
-    part2(&mut instructions)?;
+    println!("Part 2: {}", part2(&instructions)?);
     println!("Part 2 took: {:#?}", now.elapsed());
 
     Ok(())
@@ -104,20 +234,20 @@ mod tests {
 
     #[test]
     fn test_parse_input() -> Result<()> {
-        let input = parse_input(
-            "nop +0\n\
-             acc +1\n\
-             jmp +4\n\
-             acc +3\n\
-             jmp -3\n\
-             acc -99\n\
-             acc +1\n\
-             jmp -4\n\
-             acc +6",
-        )?;
+        let input = parse_input(EXAMPLE)?;
 
         assert_eq!(Instruction::Acc(3), input[3]);
 
         Ok(())
     }
+
+    #[test]
+    fn test_part2_strategies() -> Result<()> {
+        let input = parse_input(EXAMPLE)?;
+
+        assert_eq!(8, part2(&input)?);
+        assert_eq!(8, part2_reachability(&input)?);
+
+        Ok(())
+    }
 }
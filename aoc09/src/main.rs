@@ -3,6 +3,23 @@ use std::io::{self, Read};
 use std::time::Instant;
 
 use anyhow::{anyhow, Result};
+use crosscheck::{file_cases, generated_cases, Case, Command, Rng, Strategy, VerifyOpt};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+/// How many numbers precede the first one that is checked
+const PREAMBLE: usize = 25;
+
+const EXAMPLE: &str = "35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n\
+                       102\n117\n150\n182\n127\n219\n299\n277\n309\n576";
+
+/// The numbers, and the invalid number found in part 1
+type Part2Input = (Vec<usize>, usize);
 
 fn parse_input(input: &str) -> Result<Vec<usize>> {
     let result: Vec<usize> = input.lines().filter_map(|l| l.parse().ok()).collect();
@@ -40,14 +57,15 @@ fn part1(nums: &[usize], window_len: usize) -> Result<usize> {
 }
 
 fn part2(nums: &[usize], target: usize) -> Result<usize> {
-    // Brute force. Two indexes. Right until pass the target, if passed, move left up.
+    // Brute force. Two indexes. Right until pass the target, if passed, move left up. The range
+    // is nums[left..right], and must hold at least two numbers.
     let mut left = 0;
     let mut right = 1;
 
-    while left < right && right < nums.len() {
+    while right <= nums.len() {
         let sum: usize = nums.iter().skip(left).take(right - left).sum();
 
-        if sum == target {
+        if sum == target && right - left >= 2 {
             // Return sum of min and max in this range
             let (min, max) = nums
                 .iter()
@@ -67,14 +85,74 @@ fn part2(nums: &[usize], target: usize) -> Result<usize> {
     Err(anyhow!("No solution found."))
 }
 
+// Try every contiguous range of at least two numbers, in order of where it starts
+fn part2_brute_force(nums: &[usize], target: usize) -> Result<usize> {
+    for left in 0..nums.len() {
+        let mut sum = nums[left];
+        for right in left + 1..nums.len() {
+            sum += nums[right];
+            if sum == target {
+                let range = &nums[left..=right];
+                return Ok(range.iter().min().unwrap() + range.iter().max().unwrap());
+            }
+            if sum > target {
+                break;
+            }
+        }
+    }
+
+    Err(anyhow!("No solution found."))
+}
+
+fn part2_strategies() -> Vec<Strategy<Part2Input, usize>> {
+    vec![
+        Strategy::new("two-index scan", |(nums, target)| part2(nums, *target)),
+        Strategy::new("brute force", |(nums, target)| {
+            part2_brute_force(nums, *target)
+        }),
+    ]
+}
+
+fn parse_part2_input(input: &str, preamble: usize) -> Result<Part2Input> {
+    let nums = parse_input(input)?;
+    let target = part1(&nums, preamble)?;
+
+    Ok((nums, target))
+}
+
+// Random numbers, and the sum of a random range of them as the target
+fn generate(rng: &mut Rng) -> Part2Input {
+    let len = rng.range(2, 50) as usize;
+    let nums: Vec<usize> = (0..len).map(|_| rng.range(1, 100) as usize).collect();
+
+    let left = rng.index(len - 1);
+    let right = left + 1 + rng.index(len - left - 1);
+    let target = nums[left..=right].iter().sum();
+
+    (nums, target)
+}
+
+fn verify(opt: &VerifyOpt) -> Result<()> {
+    // The example uses a preamble of 5 rather than 25
+    let mut cases = vec![Case::new("example", parse_part2_input(EXAMPLE, 5)?)];
+    cases.extend(file_cases(opt, |input| parse_part2_input(input, PREAMBLE))?);
+    cases.extend(generated_cases(opt, generate));
+
+    crosscheck::run("Part 2", &part2_strategies(), &cases)
+}
+
 fn main() -> Result<()> {
+    if let Some(Command::Verify(opt)) = Opt::from_args().cmd {
+        return verify(&opt);
+    }
+
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
     let nums = parse_input(&input)?;
 
     let now = Instant::now();
 
-    let target = match part1(&nums, 25) {
+    let target = match part1(&nums, PREAMBLE) {
         Ok(v) => {
             println!("Part 1: {}, took {:#?}", v, now.elapsed());
             Some(v)
@@ -160,6 +238,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_part2_edges() -> Result<()> {
+        // A single number equal to the target is not a range
+        assert_eq!(5, part2(&[5, 1, 2, 3], 5)?);
+        // The range can end at the last number
+        assert_eq!(7, part2(&[9, 9, 3, 4], 7)?);
+        assert!(part2(&[5, 9], 5).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_find_pair() -> Result<()> {
         let nums = vec![1, 2, 3, 4, 5];
@@ -1,9 +1,27 @@
 use std::io::{self, Read};
 use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use automaton::visualize::{Color, Style, Visualizer};
+use automaton::{Adjacent, Automaton, Grid, LineOfSight, Rule, Status, Topology};
+use pnm::{FrameOpt, Image};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    /// Animate each round of the seating simulation in the terminal
+    #[structopt(long)]
+    visualize: bool,
+
+    // Frames are images of every round
+    #[structopt(flatten)]
+    frames: FrameOpt,
+}
+
+/// The colors of floor, empty seats and occupied seats
+const PALETTE: &str = "303030,2e8b57,dc143c";
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 enum Spot {
     Occupied,
     Empty,
@@ -12,198 +30,154 @@ enum Spot {
 
 #[derive(Debug)]
 struct Seating {
-    spots: Vec<Vec<Spot>>,
-    iter: usize,
+    spots: Grid<Spot>,
 }
 
-const DIRECTIONS: [(isize, isize); 8] = [
-    (-1, -1), // top left
-    (-1, 0),  // top
-    (-1, 1),  // top right
-    (0, -1),  // left
-    (0, 1),   // right
-    (1, -1),  // bottom left
-    (1, 0),   // bottom
-    (1, 1),   // bottom right
-];
-
 impl Seating {
-    fn new(input: &str) -> Seating {
-        let mut seating: Seating = Seating {
-            spots: vec![],
-            iter: 0,
-        };
+    fn new(input: &str) -> Result<Seating> {
+        let mut spots = vec![];
 
         for line in input.lines() {
             let row: Vec<Spot> = line
                 .chars()
                 .map(|c| match c {
-                    'L' => Spot::Empty,
-                    '#' => Spot::Occupied,
-                    '.' => Spot::Floor,
-                    _ => panic!("Invalid input: \"{}\"", line),
+                    'L' => Ok(Spot::Empty),
+                    '#' => Ok(Spot::Occupied),
+                    '.' => Ok(Spot::Floor),
+                    _ => Err(anyhow!("Invalid input: \"{}\"", line)),
                 })
-                .collect();
-            seating.spots.push(row);
+                .collect::<Result<_>>()?;
+            spots.push(row);
         }
 
-        seating
+        Ok(Seating {
+            spots: Grid::new(spots)?,
+        })
     }
+}
+
+fn count_occupied(spots: &Grid<Spot>) -> usize {
+    spots.iter().filter(|s| is_occupied(s)).count()
+}
+
+/// People take an empty seat with no occupied neighbors, and leave a seat once `crowded` or more
+/// of its neighbors are occupied.
+struct SeatRule {
+    crowded: usize,
+}
 
-    fn on_grid(&self, row: isize, col: isize) -> bool {
-        col >= 0
-            && col < self.spots[0].len() as isize
-            && row >= 0
-            && row < self.spots.len() as isize
+impl Rule<Spot> for SeatRule {
+    fn apply(&self, spot: &Spot, neighbors: &[Spot]) -> Spot {
+        let occupied = neighbors.iter().filter(|n| **n == Spot::Occupied).count();
+        match spot {
+            Spot::Floor => Spot::Floor,
+            Spot::Empty if occupied == 0 => Spot::Occupied,
+            Spot::Occupied if occupied >= self.crowded => Spot::Empty,
+            s => *s,
+        }
     }
 
-    fn occupied_neighbors(&self, row: isize, col: isize) -> u32 {
-        DIRECTIONS
-            .iter()
-            .map(|(drow, dcol)| {
-                if self.on_grid(row + drow, col + dcol)
-                    && self.spots[(row + drow) as usize][(col + dcol) as usize] == Spot::Occupied
-                {
-                    1
-                } else {
-                    0
-                }
-            })
-            .sum()
+    // Nobody sits on the floor
+    fn is_fixed(&self, spot: &Spot) -> bool {
+        *spot == Spot::Floor
     }
+}
+
+const STYLE: Style<Spot> = Style {
+    glyph: spot_glyph,
+    live: is_occupied,
+};
 
-    fn occupied_neighbors_sight(&self, row: isize, col: isize) -> u32 {
-        DIRECTIONS
-            .iter()
-            .map(|(drow, dcol)| {
-                let mut next_row = row + drow;
-                let mut next_col = col + dcol;
-
-                while self.on_grid(next_row, next_col)
-                    && self.spots[next_row as usize][next_col as usize] == Spot::Floor
-                {
-                    next_row += drow;
-                    next_col += dcol;
-                }
-
-                if self.on_grid(next_row, next_col)
-                    && self.spots[next_row as usize][next_col as usize] == Spot::Occupied
-                {
-                    1
-                } else {
-                    0
-                }
-            })
-            .sum()
+fn spot_glyph(spot: &Spot) -> (char, Color) {
+    match spot {
+        Spot::Occupied => ('#', Color::Red),
+        Spot::Empty => ('L', Color::Green),
+        Spot::Floor => ('.', Color::DarkGrey),
     }
+}
 
-    // Perform one iteration of the part 1 rules
-    fn evolve(&mut self) -> bool {
-        let mut next = self.spots.clone();
-
-        let mut changed = false;
-
-        for row in 0..self.spots.len() {
-            for col in 0..self.spots[0].len() {
-                match self.spots[row][col] {
-                    Spot::Floor => continue,
-                    Spot::Empty => {
-                        if self.occupied_neighbors(row as isize, col as isize) == 0 {
-                            next[row][col] = Spot::Occupied;
-                            changed = true;
-                        }
-                    }
-                    Spot::Occupied => {
-                        if self.occupied_neighbors(row as isize, col as isize) >= 4 {
-                            next[row][col] = Spot::Empty;
-                            changed = true;
-                        }
-                    }
-                }
-            }
-        }
+fn is_occupied(spot: &Spot) -> bool {
+    *spot == Spot::Occupied
+}
 
-        if changed {
-            self.iter += 1;
+// Palette indices: floor, empty, occupied
+fn seating_image(spots: &Grid<Spot>) -> Image {
+    Image::from_fn(spots.cols(), spots.rows(), |x, y| {
+        match spots.get(y as isize, x as isize) {
+            Some(Spot::Empty) => 1,
+            Some(Spot::Occupied) => 2,
+            _ => 0,
         }
+    })
+}
 
-        self.spots = next;
-        changed
-    }
+// Run the simulation until nobody moves
+fn settle<T>(seating: &Seating, topology: T, crowded: usize, part: &str, opt: &Opt) -> Result<usize>
+where
+    T: Topology<Grid<Spot>>,
+{
+    let mut frames = opt.frames.writer(part, PALETTE, 4)?;
+
+    let mut viewer = if opt.visualize {
+        Some(Visualizer::new(&STYLE)?)
+    } else {
+        None
+    };
 
-    // Perform one iteration of the part 2 rules
-    fn evolve2(&mut self) -> bool {
-        let mut next = self.spots.clone();
-
-        let mut changed = false;
-
-        for row in 0..self.spots.len() {
-            for col in 0..self.spots[0].len() {
-                match self.spots[row][col] {
-                    Spot::Floor => continue,
-                    Spot::Empty => {
-                        if self.occupied_neighbors_sight(row as isize, col as isize) == 0 {
-                            next[row][col] = Spot::Occupied;
-                            changed = true;
-                        }
-                    }
-                    Spot::Occupied => {
-                        if self.occupied_neighbors_sight(row as isize, col as isize) >= 5 {
-                            next[row][col] = Spot::Empty;
-                            changed = true;
-                        }
-                    }
-                }
+    let mut automaton = Automaton::new(seating.spots.clone(), topology, SeatRule { crowded });
+    let status = loop {
+        if let Some(frames) = frames.as_mut() {
+            frames.write(&seating_image(automaton.space()))?;
+        }
+        if let Some(v) = viewer.as_mut() {
+            if !v.frame(automaton.space(), automaton.generation())? {
+                viewer = None;
             }
         }
-
-        if changed {
-            self.iter += 1;
+        match automaton.step() {
+            Status::Changed => (),
+            status => break status,
         }
-
-        self.spots = next;
-        changed
+    };
+    if let Some(v) = viewer {
+        v.finish(automaton.space(), automaton.generation(), status)?;
     }
 
-    fn count_occupied(&self) -> usize {
-        self.spots
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|s| if *s == Spot::Occupied { 1 } else { 0 })
-                    .sum::<usize>()
-            })
-            .sum()
-    }
+    Ok(count_occupied(automaton.space()))
 }
 
-fn part1(seating: &mut Seating) -> Result<usize> {
-    while seating.evolve() {}
-
-    Ok(seating.count_occupied())
+// Look at the adjacent seats
+fn part1(seating: &Seating, opt: &Opt) -> Result<usize> {
+    settle(seating, Adjacent, 4, "part1", opt)
 }
 
-fn part2(seating: &mut Seating) -> Result<usize> {
-    while seating.evolve2() {}
-
-    Ok(seating.count_occupied())
+// Look at the first seat visible in each direction, ignoring floor
+fn part2(seating: &Seating, opt: &Opt) -> Result<usize> {
+    settle(
+        seating,
+        LineOfSight(|s: &Spot| *s == Spot::Floor),
+        5,
+        "part2",
+        opt,
+    )
 }
 
 fn main() -> Result<()> {
+    let opt = Opt::from_args();
+
     // Process input
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
-    let mut seating = Seating::new(&input);
+    let seating = Seating::new(&input)?;
 
     let now = Instant::now();
-    match part1(&mut seating) {
+    match part1(&seating, &opt) {
         Ok(v) => println!("Part 1: {}, took {:#?}", v, now.elapsed()),
         Err(e) => eprintln!("{}", e),
     };
 
-    let mut seating = Seating::new(&input);
     let now = Instant::now();
-    match part2(&mut seating) {
+    match part2(&seating, &opt) {
         Ok(v) => println!("Part 2: {}, took {:#?}", v, now.elapsed()),
         Err(e) => eprintln!("{}", e),
     };
@@ -217,6 +191,23 @@ mod tests {
 
     #[test]
     fn test_example() -> Result<()> {
+        let seating = Seating::new(
+            "L.LL.LL.LL\n\
+             LLLLLLL.LL\n\
+             L.L.L..L..\n\
+             LLLL.LL.LL\n\
+             L.LL.LL.LL\n\
+             L.LLLLL.LL\n\
+             ..L.L.....\n\
+             LLLLLLLLLL\n\
+             L.LLLLLL.L\n\
+             L.LLLLL.LL",
+        )?;
+
+        let opt = Opt::from_iter(&["aoc11"]);
+        assert_eq!(37, part1(&seating, &opt)?);
+        assert_eq!(26, part2(&seating, &opt)?);
+
         Ok(())
     }
 }
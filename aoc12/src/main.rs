@@ -1,8 +1,41 @@
+use std::fs;
 use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 use anyhow::{anyhow, Result};
 use ndarray::{arr1, arr2};
+use structopt::StructOpt;
+
+mod svg;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    /// Write SVG drawings of both routes (part1.svg and part2.svg) into this directory
+    #[structopt(long, parse(from_os_str))]
+    svg: Option<PathBuf>,
+}
+
+/// Every position the ship visited, starting at the origin, and for the waypoint mode, every
+/// absolute position of the waypoint.
+#[derive(Debug, Default, PartialEq)]
+pub struct Route {
+    pub ship: Vec<(isize, isize)>,
+    pub waypoint: Option<Vec<(isize, isize)>>,
+}
+
+impl Route {
+    /// Where the ship ended up.
+    pub fn end(&self) -> (isize, isize) {
+        self.ship.last().copied().unwrap_or((0, 0))
+    }
+
+    /// Manhattan distance of the final position from the origin.
+    pub fn distance(&self) -> isize {
+        let (x, y) = self.end();
+        x.abs() + y.abs()
+    }
+}
 
 fn parse_input(input: &str) -> Result<Vec<(char, isize)>> {
     let result = input
@@ -18,10 +51,15 @@ fn parse_input(input: &str) -> Result<Vec<(char, isize)>> {
     Ok(result)
 }
 
-fn part1(actions: &Vec<(char, isize)>) -> Result<isize> {
+// The ship moves itself, and F moves it in the direction it is facing
+fn navigate(actions: &[(char, isize)]) -> Result<Route> {
     const DIRECTIONS: [char; 4] = ['N', 'E', 'S', 'W'];
     let (mut x, mut y) = (0, 0);
     let mut dir_idx = 1;
+    let mut route = Route {
+        ship: vec![(0, 0)],
+        waypoint: None,
+    };
 
     for action in actions {
         match action.0 {
@@ -52,15 +90,22 @@ fn part1(actions: &Vec<(char, isize)>) -> Result<isize> {
             },
             _ => return Err(anyhow!("Unexpected action: {:#?}", action)),
         }
+
+        if route.end() != (x, y) {
+            route.ship.push((x, y));
+        }
     }
 
-    Ok(x.abs() + y.abs())
+    Ok(route)
 }
 
-fn part2(actions: &Vec<(char, isize)>) -> Result<isize> {
+// N, S, E, W and rotations move the waypoint, and F moves the ship towards it
+fn navigate_waypoint(actions: &[(char, isize)]) -> Result<Route> {
     let (mut ship_x, mut ship_y) = (0, 0);
 
     let mut waypoint = arr1(&[10, 1]);
+    let mut ship = vec![(0, 0)];
+    let mut waypoints = vec![(10, 1)];
 
     // counter-clockwise rotation matrix
     let rotation = arr2(&[[0, 1], [-1, 0]]);
@@ -91,12 +136,50 @@ fn part2(actions: &Vec<(char, isize)>) -> Result<isize> {
             }
             _ => return Err(anyhow!("Unexpected action: {:#?}", action)),
         }
+
+        if ship.last() != Some(&(ship_x, ship_y)) {
+            ship.push((ship_x, ship_y));
+        }
+        let absolute = (ship_x + waypoint[0], ship_y + waypoint[1]);
+        if waypoints.last() != Some(&absolute) {
+            waypoints.push(absolute);
+        }
     }
 
-    Ok(ship_x.abs() + ship_y.abs())
+    Ok(Route {
+        ship,
+        waypoint: Some(waypoints),
+    })
+}
+
+fn part1(actions: &[(char, isize)]) -> Result<isize> {
+    Ok(navigate(actions)?.distance())
+}
+
+fn part2(actions: &[(char, isize)]) -> Result<isize> {
+    Ok(navigate_waypoint(actions)?.distance())
+}
+
+fn write_svgs(actions: &[(char, isize)], dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let part1 = dir.join("part1.svg");
+    fs::write(&part1, svg::render("Part 1: heading", &navigate(actions)?)?)?;
+    println!("Wrote {}", part1.display());
+
+    let part2 = dir.join("part2.svg");
+    fs::write(
+        &part2,
+        svg::render("Part 2: waypoint", &navigate_waypoint(actions)?)?,
+    )?;
+    println!("Wrote {}", part2.display());
+
+    Ok(())
 }
 
 fn main() -> Result<()> {
+    let opt = Opt::from_args();
+
     // Process input
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
@@ -114,6 +197,10 @@ fn main() -> Result<()> {
         Err(e) => eprintln!("{}", e),
     };
 
+    if let Some(dir) = &opt.svg {
+        write_svgs(&actions, dir)?;
+    }
+
     Ok(())
 }
 
@@ -130,4 +217,52 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_routes() -> Result<()> {
+        let actions = parse_input("F10\nN3\nF7\nR90\nF11")?;
+
+        let route = navigate(&actions)?;
+        assert_eq!(
+            vec![(0, 0), (10, 0), (10, 3), (17, 3), (17, -8)],
+            route.ship
+        );
+        assert_eq!(25, route.distance());
+
+        let route = navigate_waypoint(&actions)?;
+        assert_eq!(vec![(0, 0), (100, 10), (170, 38), (214, -72)], route.ship);
+        assert_eq!(
+            Some(vec![
+                (10, 1),
+                (110, 11),
+                (110, 14),
+                (180, 42),
+                (174, 28),
+                (218, -82)
+            ]),
+            route.waypoint
+        );
+        assert_eq!(286, route.distance());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_svg() -> Result<()> {
+        let actions = parse_input("F10\nN3\nF7\nR90\nF11")?;
+        let svg = svg::render("Part 1: heading", &navigate(&actions)?)?;
+
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="960" height="678" viewBox="0 0 960 678""#
+        ));
+        assert!(svg.contains(
+            r#"<polyline points="80.0,221.2 550.6,221.2 550.6,80.0 880.0,80.0 880.0,597.6" fill="none" stroke="steelblue""#
+        ));
+        assert!(svg.contains("end (17, -8), distance 25"));
+        // No waypoint in part 1
+        assert!(!svg.contains("darkorange"));
+        assert!(svg.ends_with("</svg>\n"));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,182 @@
+//! Draw a [`Route`] as a standalone SVG image, with north pointing up.
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use crate::Route;
+
+/// Length in pixels of the longer side of the bounding box
+const SIZE: f64 = 800.0;
+const MARGIN: f64 = 80.0;
+
+struct Frame {
+    min_x: isize,
+    max_y: isize,
+    scale: f64,
+}
+
+impl Frame {
+    // Map ship coordinates to SVG coordinates, which grow downwards
+    fn map(&self, (x, y): (isize, isize)) -> (f64, f64) {
+        (
+            MARGIN + (x - self.min_x) as f64 * self.scale,
+            MARGIN + (self.max_y - y) as f64 * self.scale,
+        )
+    }
+
+    fn polyline(&self, points: &[(isize, isize)]) -> String {
+        points
+            .iter()
+            .map(|p| {
+                let (x, y) = self.map(*p);
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+pub fn render(title: &str, route: &Route) -> Result<String> {
+    let waypoint = route.waypoint.as_deref().unwrap_or(&[]);
+    let points = route.ship.iter().chain(waypoint).chain(&[(0, 0)]);
+    let min_x = points.clone().map(|p| p.0).min().unwrap_or(0);
+    let max_x = points.clone().map(|p| p.0).max().unwrap_or(0);
+    let min_y = points.clone().map(|p| p.1).min().unwrap_or(0);
+    let max_y = points.map(|p| p.1).max().unwrap_or(0);
+
+    let span = (max_x - min_x).max(max_y - min_y).max(1);
+    let frame = Frame {
+        min_x,
+        max_y,
+        scale: SIZE / span as f64,
+    };
+    let (left, top) = frame.map((min_x, max_y));
+    let (right, bottom) = frame.map((max_x, min_y));
+    let (origin_x, origin_y) = frame.map((0, 0));
+    let end = route.end();
+    let (end_x, end_y) = frame.map(end);
+
+    let (width, height) = (right + MARGIN, bottom + MARGIN);
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.0} {:.0}" font-family="monospace" font-size="12">"#,
+        width, height, width, height
+    )?;
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+    writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" font-size="16">{}</text>"#,
+        left,
+        MARGIN / 2.0,
+        title
+    )?;
+
+    // Bounding box, labelled at two corners
+    writeln!(
+        svg,
+        r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="none" stroke="gray" stroke-dasharray="4 4"/>"#,
+        left,
+        top,
+        right - left,
+        bottom - top
+    )?;
+    writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" fill="gray" text-anchor="end">({}, {})</text>"#,
+        right,
+        top - 6.0,
+        max_x,
+        max_y
+    )?;
+    writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" fill="gray">({}, {})</text>"#,
+        left,
+        bottom + 16.0,
+        min_x,
+        min_y
+    )?;
+
+    // Axes through the origin
+    writeln!(
+        svg,
+        r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="black" stroke-width="0.5"/>"#,
+        left, origin_y, right, origin_y
+    )?;
+    writeln!(
+        svg,
+        r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="black" stroke-width="0.5"/>"#,
+        origin_x, top, origin_x, bottom
+    )?;
+    writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}">east</text>"#,
+        right + 6.0,
+        origin_y + 4.0
+    )?;
+    writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">north</text>"#,
+        origin_x,
+        top - 20.0
+    )?;
+
+    if !waypoint.is_empty() {
+        writeln!(
+            svg,
+            r#"<polyline points="{}" fill="none" stroke="darkorange" stroke-width="1" stroke-dasharray="3 2"/>"#,
+            frame.polyline(waypoint)
+        )?;
+    }
+    writeln!(
+        svg,
+        r#"<polyline points="{}" fill="none" stroke="steelblue" stroke-width="2"/>"#,
+        frame.polyline(&route.ship)
+    )?;
+
+    writeln!(
+        svg,
+        r#"<circle cx="{:.1}" cy="{:.1}" r="4" fill="seagreen"/>"#,
+        origin_x, origin_y
+    )?;
+    writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" fill="seagreen">origin (0, 0)</text>"#,
+        origin_x + 6.0,
+        origin_y - 6.0
+    )?;
+    writeln!(
+        svg,
+        r#"<circle cx="{:.1}" cy="{:.1}" r="4" fill="crimson"/>"#,
+        end_x, end_y
+    )?;
+    writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" fill="crimson">end ({}, {}), distance {}</text>"#,
+        end_x + 6.0,
+        end_y - 6.0,
+        end.0,
+        end.1,
+        route.distance()
+    )?;
+
+    // Legend
+    writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" fill="steelblue">ship</text>"#,
+        left,
+        bottom + 36.0
+    )?;
+    if !waypoint.is_empty() {
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" fill="darkorange">waypoint</text>"#,
+            left + 48.0,
+            bottom + 36.0
+        )?;
+    }
+    svg.push_str("</svg>\n");
+
+    Ok(svg)
+}
@@ -1,10 +1,19 @@
-use std::convert::TryInto;
 use std::io::{self, Read};
 use std::str::FromStr;
 use std::time::Instant;
 
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
+use crosscheck::{file_cases, generated_cases, Case, Command, Rng, Strategy, VerifyOpt};
 use num::Integer;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+const EXAMPLE: &str = "939\n7,13,x,x,59,x,31,19";
 
 #[derive(Debug)]
 struct Input {
@@ -19,8 +28,7 @@ impl FromStr for Input {
         let target = s.lines().next().unwrap().parse()?;
         let buses: Vec<(usize, isize)> = s
             .lines()
-            .skip(1)
-            .next()
+            .nth(1)
             .unwrap()
             .split(',')
             .enumerate()
@@ -80,7 +88,95 @@ fn part2(input: &Input) -> Result<usize> {
     Ok(t)
 }
 
+// Solve t + idx = 0 (mod id) for every bus with the Chinese remainder theorem. Bus IDs must be
+// pairwise coprime.
+fn part2_crt(input: &Input) -> Result<usize> {
+    let mut t: i128 = 0;
+    let mut modulus: i128 = 1;
+
+    for (idx, bus_id) in input.buses.iter() {
+        let id = *bus_id as i128;
+        let residue = (-(*idx as i128)).rem_euclid(id);
+
+        // Find k such that t + k * modulus = residue (mod id)
+        let gcd = modulus.extended_gcd(&id);
+        if gcd.gcd != 1 {
+            return Err(anyhow!("Bus IDs {} and {} are not coprime", modulus, id));
+        }
+        let k = ((residue - t) * gcd.x).rem_euclid(id);
+        t += k * modulus;
+        modulus *= id;
+    }
+
+    // The puzzle asks for a positive timestamp
+    if t == 0 {
+        t = modulus;
+    }
+    Ok(t as usize)
+}
+
+// Try every timestamp in turn
+fn part2_brute_force(input: &Input) -> Result<usize> {
+    (1..)
+        .find(|t| {
+            input
+                .buses
+                .iter()
+                .all(|(idx, id)| (t + idx) % *id as usize == 0)
+        })
+        .ok_or_else(|| anyhow!("No solution found."))
+}
+
+fn part2_strategies() -> Vec<Strategy<Input, usize>> {
+    vec![
+        Strategy::new("lcm sieve", part2),
+        Strategy::new("chinese remainder theorem", part2_crt),
+        // The answer can be as large as the product of the IDs
+        Strategy::new("brute force", part2_brute_force).only_if(|input| {
+            input
+                .buses
+                .iter()
+                .try_fold(1usize, |acc, (_, id)| acc.checked_mul(*id as usize))
+                .is_some_and(|product| product <= 10_000_000)
+        }),
+    ]
+}
+
+// A schedule of distinct primes, with some out of service buses in between
+fn generate(rng: &mut Rng) -> Input {
+    let mut primes = vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
+    rng.shuffle(&mut primes);
+    let count = rng.range(1, 7) as usize;
+
+    let mut idx = 0;
+    let buses = primes[..count]
+        .iter()
+        .map(|id| {
+            let bus = (idx, *id);
+            idx += 1 + rng.index(4);
+            bus
+        })
+        .collect();
+
+    Input {
+        target: rng.range(1, 1000) as isize,
+        buses,
+    }
+}
+
+fn verify(opt: &VerifyOpt) -> Result<()> {
+    let mut cases = vec![Case::new("example", EXAMPLE.parse()?)];
+    cases.extend(file_cases(opt, str::parse)?);
+    cases.extend(generated_cases(opt, generate));
+
+    crosscheck::run("Part 2", &part2_strategies(), &cases)
+}
+
 fn main() -> Result<()> {
+    if let Some(Command::Verify(opt)) = Opt::from_args().cmd {
+        return verify(&opt);
+    }
+
     // Process input
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
@@ -109,4 +205,15 @@ mod tests {
     fn test_parse_input() -> Result<()> {
         Ok(())
     }
+
+    #[test]
+    fn test_part2_strategies() -> Result<()> {
+        let input: Input = EXAMPLE.parse()?;
+
+        for strategy in part2_strategies() {
+            assert_eq!(1068781, (strategy.run)(&input)?, "{}", strategy.name);
+        }
+
+        Ok(())
+    }
 }
@@ -1,190 +1,183 @@
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
-use std::convert::TryInto;
 use std::io::{self, Read};
 use std::time::Instant;
 
 use anyhow::{anyhow, Result};
-use itertools::Itertools;
+use automaton::visualize::{Color, Style, Visualizer};
+use automaton::{Automaton, Lattice, Moore, Status};
+use pnm::{FrameOpt, Image};
+use structopt::StructOpt;
+
+const CYCLES: usize = 6;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    /// Animate each boot cycle in the terminal, one panel per slice
+    #[structopt(long)]
+    visualize: bool,
+
+    // Frames are images of every cycle, with one tile per slice
+    #[structopt(flatten)]
+    frames: FrameOpt,
+}
+
+/// The colors of inactive cubes, active cubes and tile borders
+const PALETTE: &str = "202020,00ced1,808080";
 
 #[derive(Debug)]
 struct Conway {
-    cubes: HashMap<Vec<isize>, bool>,
-    iter: isize,
-    init_dim: isize,
-    dim: isize,
-    directions: Vec<Vec<isize>>,
+    cubes: Lattice<bool>,
 }
 
 impl Conway {
-    fn new(input: &str, dim: isize) -> Result<Conway> {
-        // Assume a square input
-        let mut cubes: HashMap<_, _> = HashMap::new();
-        let init_dim = input
-            .lines()
-            .next()
-            .unwrap()
-            .chars()
-            .count()
-            .try_into()
-            .unwrap();
-
-        for (y, line) in input.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                let mut coords = vec![x.try_into().unwrap(), y.try_into().unwrap()];
-                for _ in 2..dim {
-                    coords.push(0);
-                }
-
-                match c {
-                    '#' => cubes.insert(coords, true),
-                    '.' => cubes.insert(coords, false),
-                    _ => return Err(anyhow!("Invalid input: \"{}\"", line)),
-                };
-            }
+    fn new(input: &str, dim: usize) -> Result<Conway> {
+        let mut rows = vec![];
+
+        for line in input.lines() {
+            let row: Vec<bool> = line
+                .chars()
+                .map(|c| match c {
+                    '#' => Ok(true),
+                    '.' => Ok(false),
+                    _ => Err(anyhow!("Invalid input: \"{}\"", line)),
+                })
+                .collect::<Result<_>>()?;
+            rows.push(row);
         }
 
         Ok(Conway {
-            cubes,
-            iter: 1,
-            init_dim,
-            dim,
-            directions: (0..dim)
-                .map(|_| (-1..=1))
-                .multi_cartesian_product()
-                .into_iter()
-                .filter(|v| !v.iter().all(|n| *n == 0))
-                .collect(),
+            cubes: Lattice::from_plane(rows, dim),
         })
     }
 
-    fn occupied_neighbors(&self, coords: &[isize]) -> usize {
-        self.directions
-            .iter()
-            .map(|v| {
-                let key: Vec<isize> = v.iter().zip(coords).map(|(v, c)| c + *v).collect();
-                match self.cubes.get(&key) {
-                    Some(true) => 1,
-                    _ => 0,
-                }
-            })
-            .sum()
-    }
-
-    // Perform one iteration of the part 1 rules
-    fn evolve(&mut self) {
-        let mut next = self.cubes.clone();
-
-        for x in -self.iter..=self.init_dim + self.iter {
-            for y in -self.iter..=self.init_dim + self.iter {
-                for z in -self.iter..=self.iter {
-                    let coords = vec![x, y, z];
-                    match next.entry(coords.clone()) {
-                        Entry::Occupied(mut v) => match v.get() {
-                            true => match self.occupied_neighbors(&coords) {
-                                2 | 3 => (),
-                                _ => {
-                                    let _ = v.insert(false);
-                                }
-                            },
-                            false => match self.occupied_neighbors(&coords) {
-                                3 => {
-                                    let _ = v.insert(true);
-                                }
-                                _ => (),
-                            },
-                        },
-                        Entry::Vacant(v) => match self.occupied_neighbors(&coords) {
-                            3 => {
-                                let _ = v.insert(true);
-                            }
-                            _ => (),
-                        },
-                    }
-                }
+    // Run the boot process for the given number of cycles
+    fn boot(&self, cycles: usize, opt: &Opt) -> Result<Lattice<bool>> {
+        let dim = self.cubes.dim();
+        let mut frames = opt.frames.writer(&format!("{}d", dim), PALETTE, 8)?;
+
+        // The pocket dimension grows by at most one cube in every direction each cycle, so size
+        // every frame to fit the final cycle.
+        let (mut min, mut max) = self
+            .cubes
+            .bounds()
+            .unwrap_or_else(|| (vec![0; dim], vec![0; dim]));
+        min.iter_mut().for_each(|v| *v -= cycles as isize);
+        max.iter_mut().for_each(|v| *v += cycles as isize);
+
+        let mut viewer = if opt.visualize {
+            Some(Visualizer::new(&STYLE)?)
+        } else {
+            None
+        };
+
+        let mut automaton = Automaton::new(self.cubes.clone(), Moore::new(dim), cube_rule);
+        let mut status = Status::Changed;
+        for cycle in 0..=cycles {
+            if let Some(frames) = frames.as_mut() {
+                frames.write(&cubes_image(automaton.space(), &min, &max))?;
             }
-        }
-
-        self.iter += 1;
-        self.cubes = next;
-    }
-
-    // Perform one iteration of the part 1 rules
-    fn evolve2(&mut self) {
-        let mut next = self.cubes.clone();
-
-        for x in -self.iter..=self.init_dim + self.iter {
-            for y in -self.iter..=self.init_dim + self.iter {
-                for z in -self.iter..=self.iter {
-                    for w in -self.iter..=self.iter {
-                        let coords = vec![x, y, z, w];
-                        match next.entry(coords.clone()) {
-                            Entry::Occupied(mut v) => match v.get() {
-                                true => match self.occupied_neighbors(&coords) {
-                                    2 | 3 => (),
-                                    _ => {
-                                        let _ = v.insert(false);
-                                    }
-                                },
-                                false => match self.occupied_neighbors(&coords) {
-                                    3 => {
-                                        let _ = v.insert(true);
-                                    }
-                                    _ => (),
-                                },
-                            },
-                            Entry::Vacant(v) => match self.occupied_neighbors(&coords) {
-                                3 => {
-                                    let _ = v.insert(true);
-                                }
-                                _ => (),
-                            },
-                        }
-                    }
+            if cycle == cycles {
+                break;
+            }
+            if let Some(v) = viewer.as_mut() {
+                if !v.frame(automaton.space(), cycle)? {
+                    viewer = None;
                 }
             }
+            status = automaton.step();
+        }
+        if let Some(v) = viewer {
+            v.finish(automaton.space(), cycles, status)?;
         }
 
-        self.iter += 1;
-        self.cubes = next;
+        Ok(automaton.space().clone())
     }
+}
 
-    fn count_occupied(&self) -> usize {
-        self.cubes.values().map(|b| *b as usize).sum()
+// One tile per (x, y) slice, with z increasing left to right and any further axes top to bottom.
+// Palette indices: inactive, active, border.
+fn cubes_image(cubes: &Lattice<bool>, min: &[isize], max: &[isize]) -> Image {
+    let tile_width = (max[0] - min[0] + 1) as usize;
+    let tile_height = (max[1] - min[1] + 1) as usize;
+    let slices = cubes.slices(min, max);
+    let columns = if cubes.dim() > 2 {
+        (max[2] - min[2] + 1) as usize
+    } else {
+        1
+    };
+    let rows = slices.len() / columns;
+
+    let mut image = Image::from_fn(
+        columns * (tile_width + 1) + 1,
+        rows * (tile_height + 1) + 1,
+        |_, _| 2,
+    );
+    for (i, slice) in slices.iter().enumerate() {
+        let tile = Image::from_fn(tile_width, tile_height, |x, y| {
+            let mut coord = vec![min[0] + x as isize, min[1] + y as isize];
+            coord.extend(slice);
+            cubes.get(&coord).map_or(0, |active| *active as usize)
+        });
+        image.blit(
+            &tile,
+            (i / rows) * (tile_width + 1) + 1,
+            (i % rows) * (tile_height + 1) + 1,
+        );
     }
+
+    image
 }
 
-fn part1(input: &mut Conway) -> Result<usize> {
-    for _ in 0..6 {
-        input.evolve();
+const STYLE: Style<bool> = Style {
+    glyph: cube_glyph,
+    live: is_active,
+};
+
+fn cube_glyph(active: &bool) -> (char, Color) {
+    if *active {
+        ('#', Color::Cyan)
+    } else {
+        ('.', Color::DarkGrey)
     }
+}
 
-    Ok(input.count_occupied())
+fn is_active(active: &bool) -> bool {
+    *active
 }
 
-fn part2(input: &mut Conway) -> Result<usize> {
-    for _ in 0..6 {
-        input.evolve2();
-    }
+// An active cube stays active with 2 or 3 active neighbors, an inactive cube becomes active with
+// exactly 3.
+fn cube_rule(active: &bool, neighbors: &[bool]) -> bool {
+    matches!(
+        (active, neighbors.iter().filter(|n| **n).count()),
+        (true, 2) | (_, 3)
+    )
+}
+
+fn part1(input: &Conway, opt: &Opt) -> Result<usize> {
+    Ok(input.boot(CYCLES, opt)?.len())
+}
 
-    Ok(input.count_occupied())
+fn part2(input: &Conway, opt: &Opt) -> Result<usize> {
+    Ok(input.boot(CYCLES, opt)?.len())
 }
 
 fn main() -> Result<()> {
+    let opt = Opt::from_args();
+
     // Process input
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
 
-    let mut conway = Conway::new(&input, 3)?;
+    let conway = Conway::new(&input, 3)?;
     let now = Instant::now();
-    match part1(&mut conway) {
+    match part1(&conway, &opt) {
         Ok(v) => println!("Part 1: {}, took {:#?}", v, now.elapsed()),
         Err(e) => eprintln!("{}", e),
     };
 
-    let mut conway = Conway::new(&input, 4)?;
+    let conway = Conway::new(&input, 4)?;
     let now = Instant::now();
-    match part2(&mut conway) {
+    match part2(&conway, &opt) {
         Ok(v) => println!("Part 2: {}, took {:#?}", v, now.elapsed()),
         Err(e) => eprintln!("{}", e),
     };
@@ -197,12 +190,13 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_itertools() -> Result<()> {
-        let combos = (0..=3).map(|_| (-1..=1)).multi_cartesian_product();
-        combos.clone().for_each(|c| {
-            let _ = dbg!(&c);
-        });
-        assert_eq!(27, combos.count());
+    fn test_example() -> Result<()> {
+        let input = ".#.\n..#\n###";
+
+        let opt = Opt::from_iter(&["aoc17"]);
+        assert_eq!(112, part1(&Conway::new(input, 3)?, &opt)?);
+        assert_eq!(848, part2(&Conway::new(input, 4)?, &opt)?);
+
         Ok(())
     }
 }
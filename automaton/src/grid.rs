@@ -0,0 +1,191 @@
+//! A dense, bounded two-dimensional space.
+use std::hash::{Hash, Hasher};
+
+use anyhow::{anyhow, Result};
+
+use crate::{Fingerprinter, Space, Topology};
+
+/// The eight directions around a cell, as (row, col) offsets.
+pub const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1), // top left
+    (-1, 0),  // top
+    (-1, 1),  // top right
+    (0, -1),  // left
+    (0, 1),   // right
+    (1, -1),  // bottom left
+    (1, 0),   // bottom
+    (1, 1),   // bottom right
+];
+
+/// A fixed-size grid of cells, stored row-major.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<C> {
+    cells: Vec<C>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<C> Grid<C> {
+    /// Build a grid from its rows, which must all be the same length.
+    pub fn new(rows: Vec<Vec<C>>) -> Result<Grid<C>> {
+        let num_rows = rows.len();
+        let num_cols = rows.first().map_or(0, |r| r.len());
+
+        let mut cells = Vec::with_capacity(num_rows * num_cols);
+        for (i, row) in rows.into_iter().enumerate() {
+            if row.len() != num_cols {
+                return Err(anyhow!(
+                    "Row {} has {} cells, expected {}",
+                    i,
+                    row.len(),
+                    num_cols
+                ));
+            }
+            cells.extend(row);
+        }
+
+        Ok(Grid {
+            cells,
+            rows: num_rows,
+            cols: num_cols,
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The cell at (row, col), or None if it is off the grid.
+    pub fn get(&self, row: isize, col: isize) -> Option<&C> {
+        if row >= 0 && col >= 0 && (row as usize) < self.rows && (col as usize) < self.cols {
+            Some(&self.cells[row as usize * self.cols + col as usize])
+        } else {
+            None
+        }
+    }
+
+    /// The cells of a single row.
+    pub fn row(&self, row: usize) -> &[C] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Iterate over every cell, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = &C> {
+        self.cells.iter()
+    }
+}
+
+impl<C> Space for Grid<C>
+where
+    C: Clone + PartialEq + Hash,
+{
+    type Coord = (usize, usize);
+    type Cell = C;
+
+    fn coords(&self) -> Vec<(usize, usize)> {
+        (0..self.rows)
+            .flat_map(|row| (0..self.cols).map(move |col| (row, col)))
+            .collect()
+    }
+
+    fn get(&self, coord: &(usize, usize)) -> C {
+        self.cells[coord.0 * self.cols + coord.1].clone()
+    }
+
+    fn set(&mut self, coord: &(usize, usize), cell: C) {
+        self.cells[coord.0 * self.cols + coord.1] = cell;
+    }
+
+    // Every cell is rewritten each generation, so there is nothing to clear.
+    fn reset_from(&mut self, _current: &Grid<C>) {}
+
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = Fingerprinter::default();
+        self.cells.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// The eight cells immediately surrounding a cell.
+#[derive(Debug, Clone, Copy)]
+pub struct Adjacent;
+
+impl<C> Topology<Grid<C>> for Adjacent
+where
+    C: Clone + PartialEq + Hash,
+{
+    fn neighbors(&self, grid: &Grid<C>, coord: &(usize, usize), out: &mut Vec<C>) {
+        let (row, col) = (coord.0 as isize, coord.1 as isize);
+        for (drow, dcol) in DIRECTIONS.iter() {
+            if let Some(cell) = grid.get(row + drow, col + dcol) {
+                out.push(cell.clone());
+            }
+        }
+    }
+}
+
+/// The first cell visible in each of the eight directions, looking past any cell for which the
+/// predicate returns true.
+#[derive(Debug, Clone, Copy)]
+pub struct LineOfSight<F>(pub F);
+
+impl<C, F> Topology<Grid<C>> for LineOfSight<F>
+where
+    C: Clone + PartialEq + Hash,
+    F: Fn(&C) -> bool,
+{
+    fn neighbors(&self, grid: &Grid<C>, coord: &(usize, usize), out: &mut Vec<C>) {
+        let (row, col) = (coord.0 as isize, coord.1 as isize);
+        for (drow, dcol) in DIRECTIONS.iter() {
+            let mut next_row = row + drow;
+            let mut next_col = col + dcol;
+
+            while let Some(cell) = grid.get(next_row, next_col) {
+                if !(self.0)(cell) {
+                    out.push(cell.clone());
+                    break;
+                }
+                next_row += drow;
+                next_col += dcol;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_of_sight() -> Result<()> {
+        // 0 is see-through, so the center sees both 2s and the 1 but nothing else.
+        let grid = Grid::new(vec![
+            vec![0, 0, 0, 0, 0],
+            vec![2, 0, 9, 0, 0],
+            vec![0, 1, 0, 0, 2],
+            vec![0, 0, 0, 0, 0],
+            vec![0, 0, 0, 0, 0],
+        ])?;
+
+        let mut seen = vec![];
+        LineOfSight(|c: &u8| *c == 0).neighbors(&grid, &(2, 2), &mut seen);
+        seen.sort_unstable();
+        assert_eq!(vec![1, 2, 9], seen);
+
+        let mut adjacent = vec![];
+        Adjacent.neighbors(&grid, &(2, 2), &mut adjacent);
+        assert_eq!(8, adjacent.len());
+        assert_eq!(10, adjacent.iter().sum::<u8>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ragged_grid() {
+        assert!(Grid::new(vec![vec![0], vec![0, 1]]).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! A sparse, unbounded N-dimensional space.
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::{Fingerprinter, Space, Topology};
+
+/// The offsets of every cell within one step of the origin in `dim` dimensions, excluding the
+/// origin itself.
+pub fn moore_offsets(dim: usize) -> Vec<Vec<isize>> {
+    let mut offsets = vec![vec![]];
+    for _ in 0..dim {
+        offsets = offsets
+            .into_iter()
+            .flat_map(|prefix: Vec<isize>| {
+                (-1..=1).map(move |d| {
+                    let mut next = prefix.clone();
+                    next.push(d);
+                    next
+                })
+            })
+            .collect();
+    }
+
+    offsets
+        .into_iter()
+        .filter(|v| !v.iter().all(|n| *n == 0))
+        .collect()
+}
+
+/// An infinite lattice of cells in which only cells that differ from the default (quiescent)
+/// state are stored.
+///
+/// Because quiescent cells far from any activity are never visited, rules must map a quiescent
+/// cell with only quiescent neighbors back to the quiescent state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lattice<C> {
+    dim: usize,
+    cells: HashMap<Vec<isize>, C>,
+    offsets: Vec<Vec<isize>>,
+}
+
+impl<C> Lattice<C>
+where
+    C: Clone + PartialEq + Default,
+{
+    pub fn new(dim: usize) -> Lattice<C> {
+        Lattice {
+            dim,
+            cells: HashMap::new(),
+            offsets: moore_offsets(dim),
+        }
+    }
+
+    /// Build a lattice whose only non-quiescent cells lie on the plane where every coordinate
+    /// past the first two is zero. `rows[y][x]` becomes the cell at (x, y, 0, ...).
+    pub fn from_plane(rows: Vec<Vec<C>>, dim: usize) -> Lattice<C> {
+        let mut lattice = Lattice::new(dim);
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, cell) in row.into_iter().enumerate() {
+                let mut coord = vec![0; dim];
+                coord[0] = x as isize;
+                coord[1] = y as isize;
+                lattice.insert(coord, cell);
+            }
+        }
+
+        lattice
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// The cell at `coord`, or None if it is quiescent.
+    pub fn get(&self, coord: &[isize]) -> Option<&C> {
+        self.cells.get(coord)
+    }
+
+    /// Set the cell at `coord`, forgetting it if it is quiescent.
+    pub fn insert(&mut self, coord: Vec<isize>, cell: C) {
+        if cell == C::default() {
+            self.cells.remove(&coord);
+        } else {
+            self.cells.insert(coord, cell);
+        }
+    }
+
+    /// Iterate over every non-quiescent cell, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<isize>, &C)> {
+        self.cells.iter()
+    }
+
+    /// The number of non-quiescent cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The smallest and largest coordinate along each axis over all non-quiescent cells, or None
+    /// if every cell is quiescent.
+    pub fn bounds(&self) -> Option<(Vec<isize>, Vec<isize>)> {
+        let mut coords = self.cells.keys();
+        let first = coords.next()?;
+        let (mut min, mut max) = (first.clone(), first.clone());
+        for coord in coords {
+            for (i, c) in coord.iter().enumerate() {
+                min[i] = min[i].min(*c);
+                max[i] = max[i].max(*c);
+            }
+        }
+
+        Some((min, max))
+    }
+
+    /// Every combination of the coordinates past the first two within the given bounds, i.e.
+    /// the positions of each 2D (x, y) slice.
+    pub fn slices(&self, min: &[isize], max: &[isize]) -> Vec<Vec<isize>> {
+        let mut slices = vec![vec![]];
+        for axis in 2..self.dim {
+            slices = slices
+                .into_iter()
+                .flat_map(|prefix: Vec<isize>| {
+                    (min[axis]..=max[axis]).map(move |v| {
+                        let mut next = prefix.clone();
+                        next.push(v);
+                        next
+                    })
+                })
+                .collect();
+        }
+
+        slices
+    }
+}
+
+impl<C> Space for Lattice<C>
+where
+    C: Clone + PartialEq + Default + Hash,
+{
+    type Coord = Vec<isize>;
+    type Cell = C;
+
+    // Only cells touching a non-quiescent cell can change
+    fn coords(&self) -> Vec<Vec<isize>> {
+        let mut coords: HashSet<Vec<isize>> = HashSet::new();
+        for coord in self.cells.keys() {
+            coords.insert(coord.clone());
+            for offset in &self.offsets {
+                coords.insert(coord.iter().zip(offset).map(|(c, o)| c + o).collect());
+            }
+        }
+
+        coords.into_iter().collect()
+    }
+
+    fn get(&self, coord: &Vec<isize>) -> C {
+        self.cells.get(coord).cloned().unwrap_or_default()
+    }
+
+    fn set(&mut self, coord: &Vec<isize>, cell: C) {
+        if cell != C::default() {
+            self.cells.insert(coord.clone(), cell);
+        }
+    }
+
+    fn reset_from(&mut self, _current: &Lattice<C>) {
+        self.cells.clear();
+    }
+
+    // Order-independent, since HashMap iteration order is arbitrary
+    fn fingerprint(&self) -> u64 {
+        self.cells
+            .iter()
+            .map(|entry| {
+                let mut hasher = Fingerprinter::default();
+                entry.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0, u64::wrapping_add)
+    }
+}
+
+/// Every cell within one step along each axis (the Moore neighborhood), in any number of
+/// dimensions.
+#[derive(Debug, Clone)]
+pub struct Moore {
+    offsets: Vec<Vec<isize>>,
+}
+
+impl Moore {
+    pub fn new(dim: usize) -> Moore {
+        Moore {
+            offsets: moore_offsets(dim),
+        }
+    }
+}
+
+impl<C> Topology<Lattice<C>> for Moore
+where
+    C: Clone + PartialEq + Default + Hash,
+{
+    fn neighbors(&self, lattice: &Lattice<C>, coord: &Vec<isize>, out: &mut Vec<C>) {
+        let mut key = coord.clone();
+        for offset in &self.offsets {
+            for (k, (c, o)) in key.iter_mut().zip(coord.iter().zip(offset)) {
+                *k = c + o;
+            }
+            out.push(lattice.get(&key).cloned().unwrap_or_default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moore_offsets() {
+        assert_eq!(8, moore_offsets(2).len());
+        assert_eq!(26, moore_offsets(3).len());
+        assert_eq!(80, moore_offsets(4).len());
+    }
+}
@@ -0,0 +1,365 @@
+//! A small cellular automaton engine shared by the grid simulations (day 11's seating system and
+//! day 17's Conway cubes).
+//!
+//! An [`Automaton`] is built from three pieces:
+//!
+//! - a [`Space`], which stores the cell states (e.g. a dense [`Grid`] or a sparse [`Lattice`]),
+//! - a [`Topology`], which decides which cells count as a cell's neighbors,
+//! - a [`Rule`], which maps a cell and its neighbors to the cell's next state.
+//!
+//! The engine keeps two copies of the space and swaps them each generation rather than cloning,
+//! and reports when the simulation reaches a fixed point or falls into a cycle.
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::mem;
+
+pub mod grid;
+pub mod lattice;
+pub mod visualize;
+
+pub use grid::{Adjacent, Grid, LineOfSight};
+pub use lattice::{Lattice, Moore};
+
+/// Storage for the state of every cell in the simulation.
+pub trait Space: Clone + PartialEq {
+    type Coord;
+    type Cell: Clone + PartialEq;
+
+    /// Every coordinate whose state may change in the next generation.
+    fn coords(&self) -> Vec<Self::Coord>;
+
+    /// The state of the cell at `coord`.
+    fn get(&self, coord: &Self::Coord) -> Self::Cell;
+
+    /// Set the state of the cell at `coord`.
+    fn set(&mut self, coord: &Self::Coord, cell: Self::Cell);
+
+    /// Prepare this buffer to receive the generation following `current`.
+    fn reset_from(&mut self, current: &Self);
+
+    /// A hash of the full state, used to find earlier states that may be equal to this one.
+    fn fingerprint(&self) -> u64;
+}
+
+/// A fast, non-cryptographic hasher for fingerprints, using the multiply-rotate step of the
+/// hasher in rustc.
+#[derive(Debug, Default)]
+pub(crate) struct Fingerprinter(u64);
+
+impl Fingerprinter {
+    fn add(&mut self, word: u64) {
+        self.0 = (self.0.rotate_left(5) ^ word).wrapping_mul(0x517c_c1b7_2722_0a95);
+    }
+}
+
+impl Hasher for Fingerprinter {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.add(u64::from(*byte));
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.add(n);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.add(n as u64);
+    }
+
+    fn write_isize(&mut self, n: isize) {
+        self.add(n as u64);
+    }
+}
+
+/// Which cells are considered neighbors of a given cell.
+pub trait Topology<S: Space> {
+    /// Push the states of the neighbors of `coord` into `out`.
+    fn neighbors(&self, space: &S, coord: &S::Coord, out: &mut Vec<S::Cell>);
+}
+
+/// The transition rule of the automaton.
+pub trait Rule<C> {
+    /// The next state of `cell`, given the states of its neighbors.
+    fn apply(&self, cell: &C, neighbors: &[C]) -> C;
+
+    /// Whether `cell` never changes whatever its neighbors, so they needn't be looked up.
+    fn is_fixed(&self, _cell: &C) -> bool {
+        false
+    }
+}
+
+impl<C, F> Rule<C> for F
+where
+    F: Fn(&C, &[C]) -> C,
+{
+    fn apply(&self, cell: &C, neighbors: &[C]) -> C {
+        self(cell, neighbors)
+    }
+}
+
+/// The result of advancing the automaton by one generation.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Status {
+    /// At least one cell changed, and the new state has not been seen before.
+    Changed,
+    /// No cell changed; every further generation is identical.
+    Fixed,
+    /// The new state was already seen at generation `start`, and repeats every `period`
+    /// generations.
+    Cycle { start: usize, period: usize },
+}
+
+#[derive(Debug)]
+pub struct Automaton<S, T, R> {
+    current: S,
+    next: S,
+    topology: T,
+    rule: R,
+    generation: usize,
+    /// The state at generation 0, to replay earlier generations from
+    initial: S,
+    /// The generations of every state seen so far, by fingerprint
+    seen: HashMap<u64, Vec<usize>>,
+}
+
+// Compute the generation following `current` into `next`, returning whether any cell changed
+fn advance<S, T, R>(topology: &T, rule: &R, current: &S, next: &mut S) -> bool
+where
+    S: Space,
+    T: Topology<S>,
+    R: Rule<S::Cell>,
+{
+    let mut changed = false;
+    let mut neighbors = vec![];
+
+    next.reset_from(current);
+    for coord in current.coords() {
+        let cell = current.get(&coord);
+        if rule.is_fixed(&cell) {
+            next.set(&coord, cell);
+            continue;
+        }
+
+        neighbors.clear();
+        topology.neighbors(current, &coord, &mut neighbors);
+        let cell_next = rule.apply(&cell, &neighbors);
+        if cell_next != cell {
+            changed = true;
+        }
+        next.set(&coord, cell_next);
+    }
+
+    changed
+}
+
+impl<S, T, R> Automaton<S, T, R>
+where
+    S: Space,
+    T: Topology<S>,
+    R: Rule<S::Cell>,
+{
+    pub fn new(space: S, topology: T, rule: R) -> Automaton<S, T, R> {
+        let mut seen = HashMap::new();
+        seen.insert(space.fingerprint(), vec![0]);
+
+        Automaton {
+            next: space.clone(),
+            initial: space.clone(),
+            current: space,
+            topology,
+            rule,
+            generation: 0,
+            seen,
+        }
+    }
+
+    /// The current state of the simulation.
+    pub fn space(&self) -> &S {
+        &self.current
+    }
+
+    /// The number of generations that have changed the state so far.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Advance the simulation by one generation.
+    ///
+    /// Reaching a fixed point does not count as a new generation.
+    pub fn step(&mut self) -> Status {
+        if !advance(&self.topology, &self.rule, &self.current, &mut self.next) {
+            return Status::Fixed;
+        }
+
+        mem::swap(&mut self.current, &mut self.next);
+        self.generation += 1;
+
+        // Fingerprints can collide, so only an equal state is a cycle
+        let fingerprint = self.current.fingerprint();
+        if let Some(start) = self.seen.get(&fingerprint).and_then(|g| self.replay(g)) {
+            return Status::Cycle {
+                start,
+                period: self.generation - start,
+            };
+        }
+        self.seen
+            .entry(fingerprint)
+            .or_default()
+            .push(self.generation);
+
+        Status::Changed
+    }
+
+    // The first of the (ascending) earlier generations whose state equals the current one, found
+    // by simulating again from the initial state. Only states that share a fingerprint are
+    // replayed, so this runs once per cycle, plus once per collision.
+    fn replay(&self, generations: &[usize]) -> Option<usize> {
+        let mut state = self.initial.clone();
+        let mut next = self.initial.clone();
+        let mut generation = 0;
+
+        for &target in generations {
+            while generation < target {
+                advance(&self.topology, &self.rule, &state, &mut next);
+                mem::swap(&mut state, &mut next);
+                generation += 1;
+            }
+            if state == self.current {
+                return Some(target);
+            }
+        }
+
+        None
+    }
+
+    /// Advance the simulation until it reaches a fixed point or a cycle.
+    pub fn run_until_stable(&mut self) -> Status {
+        loop {
+            match self.step() {
+                Status::Changed => (),
+                status => return status,
+            }
+        }
+    }
+
+    /// Advance the simulation by `generations` generations, stopping early at a fixed point.
+    pub fn run(&mut self, generations: usize) -> Status {
+        let mut status = Status::Changed;
+        for _ in 0..generations {
+            status = self.step();
+            if status == Status::Fixed {
+                break;
+            }
+        }
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anyhow::Result;
+
+    // Standard Game of Life on a bounded grid
+    fn life(cell: &bool, neighbors: &[bool]) -> bool {
+        matches!(
+            (cell, neighbors.iter().filter(|n| **n).count()),
+            (true, 2) | (_, 3)
+        )
+    }
+
+    #[test]
+    fn test_fixed_point() -> Result<()> {
+        // A block is a still life
+        let grid = Grid::new(vec![
+            vec![false, false, false, false],
+            vec![false, true, true, false],
+            vec![false, true, true, false],
+            vec![false, false, false, false],
+        ])?;
+        let mut automaton = Automaton::new(grid, Adjacent, life);
+
+        assert_eq!(Status::Fixed, automaton.run_until_stable());
+        assert_eq!(0, automaton.generation());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle() -> Result<()> {
+        // A blinker oscillates with period 2
+        let grid = Grid::new(vec![
+            vec![false, false, false],
+            vec![true, true, true],
+            vec![false, false, false],
+        ])?;
+        let mut automaton = Automaton::new(grid, Adjacent, life);
+
+        assert_eq!(
+            Status::Cycle {
+                start: 0,
+                period: 2
+            },
+            automaton.run_until_stable()
+        );
+        assert_eq!(2, automaton.generation());
+
+        Ok(())
+    }
+
+    // A single counter whose fingerprints all collide
+    #[derive(Debug, Clone, PartialEq)]
+    struct Counter(u32);
+
+    impl Space for Counter {
+        type Coord = ();
+        type Cell = u32;
+
+        fn coords(&self) -> Vec<()> {
+            vec![()]
+        }
+
+        fn get(&self, _coord: &()) -> u32 {
+            self.0
+        }
+
+        fn set(&mut self, _coord: &(), cell: u32) {
+            self.0 = cell;
+        }
+
+        fn reset_from(&mut self, current: &Counter) {
+            self.0 = current.0;
+        }
+
+        fn fingerprint(&self) -> u64 {
+            0
+        }
+    }
+
+    struct Single;
+
+    impl Topology<Counter> for Single {
+        fn neighbors(&self, _space: &Counter, _coord: &(), _out: &mut Vec<u32>) {}
+    }
+
+    #[test]
+    fn test_fingerprint_collision() {
+        let mut automaton = Automaton::new(Counter(0), Single, |c: &u32, _: &[u32]| (c + 1) % 3);
+
+        assert_eq!(Status::Changed, automaton.step());
+        assert_eq!(Status::Changed, automaton.step());
+        assert_eq!(
+            Status::Cycle {
+                start: 0,
+                period: 3
+            },
+            automaton.step()
+        );
+    }
+}
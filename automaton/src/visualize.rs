@@ -0,0 +1,366 @@
+//! An animated terminal view of an [`Automaton`](crate::Automaton), one frame per generation,
+//! drawn from the same loop that runs the simulation.
+//!
+//! Keys:
+//!
+//! - `space`: pause or resume
+//! - `n` or `→`: advance a single generation while paused
+//! - `+` / `-`: halve or double the delay between generations
+//! - `q` or `esc`: quit
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::style;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::{Grid, Lattice, Space, Status};
+
+pub use crossterm::style::Color;
+
+const MIN_DELAY: Duration = Duration::from_millis(10);
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// How the cells of a simulation are drawn.
+pub struct Style<C> {
+    /// The character and color used for a cell.
+    pub glyph: fn(&C) -> (char, Color),
+    /// Whether a cell counts towards the live-cell total.
+    pub live: fn(&C) -> bool,
+}
+
+/// A titled block of text, `width` cells wide, in which each row has already been colored.
+#[derive(Debug)]
+pub struct Panel {
+    pub title: String,
+    pub width: usize,
+    pub rows: Vec<String>,
+}
+
+/// Spaces which can be drawn as one or more 2D panels.
+pub trait Render: Space {
+    fn panels(&self, style: &Style<Self::Cell>) -> Vec<Panel>;
+
+    fn count_live(&self, style: &Style<Self::Cell>) -> usize;
+}
+
+fn paint<C>(cell: &C, style: &Style<C>) -> String {
+    let (c, color) = (style.glyph)(cell);
+    style::style(c).with(color).to_string()
+}
+
+impl<C> Render for Grid<C>
+where
+    C: Clone + PartialEq + std::hash::Hash,
+{
+    fn panels(&self, style: &Style<C>) -> Vec<Panel> {
+        vec![Panel {
+            title: String::new(),
+            width: self.cols(),
+            rows: (0..self.rows())
+                .map(|row| self.row(row).iter().map(|c| paint(c, style)).collect())
+                .collect(),
+        }]
+    }
+
+    fn count_live(&self, style: &Style<C>) -> usize {
+        self.iter().filter(|c| (style.live)(c)).count()
+    }
+}
+
+impl<C> Render for Lattice<C>
+where
+    C: Clone + PartialEq + Default + std::hash::Hash,
+{
+    // One panel per (x, y) slice, like the examples in the day 17 puzzle text
+    fn panels(&self, style: &Style<C>) -> Vec<Panel> {
+        let (min, max) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return vec![],
+        };
+
+        self.slices(&min, &max)
+            .into_iter()
+            .map(|slice| {
+                let rows = (min[1]..=max[1])
+                    .map(|y| {
+                        (min[0]..=max[0])
+                            .map(|x| {
+                                let mut coord = vec![x, y];
+                                coord.extend(&slice);
+                                paint(&Space::get(self, &coord), style)
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                Panel {
+                    title: slice_title(&slice),
+                    width: (max[0] - min[0] + 1) as usize,
+                    rows,
+                }
+            })
+            .collect()
+    }
+
+    fn count_live(&self, style: &Style<C>) -> usize {
+        self.iter().filter(|(_, c)| (style.live)(c)).count()
+    }
+}
+
+// e.g. "z=-1, w=2"
+fn slice_title(slice: &[isize]) -> String {
+    const AXES: [&str; 2] = ["z", "w"];
+    slice
+        .iter()
+        .enumerate()
+        .map(|(i, v)| match AXES.get(i) {
+            Some(axis) => format!("{}={}", axis, v),
+            None => format!("d{}={}", i + 2, v),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Arrange panels side by side, wrapping onto a new band of panels when `max_width` is reached.
+pub fn layout(panels: &[Panel], max_width: usize) -> Vec<String> {
+    const GAP: usize = 2;
+    let mut lines = vec![];
+    let mut band: Vec<&Panel> = vec![];
+    let mut band_width = 0;
+
+    for panel in panels {
+        let width = panel.width.max(panel.title.chars().count());
+        if !band.is_empty() && band_width + GAP + width > max_width {
+            lines.extend(layout_band(&band, GAP));
+            band.clear();
+            band_width = 0;
+        }
+        band_width += if band.is_empty() { width } else { GAP + width };
+        band.push(panel);
+    }
+    if !band.is_empty() {
+        lines.extend(layout_band(&band, GAP));
+    }
+
+    lines
+}
+
+fn layout_band(band: &[&Panel], gap: usize) -> Vec<String> {
+    let height = band.iter().map(|p| p.rows.len()).max().unwrap_or(0);
+    let has_title = band.iter().any(|p| !p.title.is_empty());
+    let mut lines = vec![];
+
+    if has_title {
+        let titles: Vec<String> = band
+            .iter()
+            .map(|p| format!("{:<width$}", p.title, width = p.width))
+            .collect();
+        lines.push(titles.join(&" ".repeat(gap)));
+    }
+    for row in 0..height {
+        let cells: Vec<String> = band
+            .iter()
+            .map(|p| {
+                let width = p.width.max(p.title.chars().count());
+                match p.rows.get(row) {
+                    Some(r) => format!("{}{}", r, " ".repeat(width - p.width)),
+                    None => " ".repeat(width),
+                }
+            })
+            .collect();
+        lines.push(cells.join(&" ".repeat(gap)));
+    }
+    lines.push(String::new());
+
+    lines
+}
+
+// Restores the terminal even if drawing fails part way through
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn draw<S: Render>(
+    space: &S,
+    style: &Style<S::Cell>,
+    generation: usize,
+    delay: Duration,
+    state: &str,
+) -> Result<()> {
+    // Some pseudo-terminals report a size of zero
+    let (cols, rows) = match terminal::size()? {
+        (0, _) | (_, 0) => (80, 24),
+        size => size,
+    };
+    let mut stdout = io::stdout();
+
+    queue!(stdout, MoveTo(0, 0), Clear(ClearType::All))?;
+    let header = format!(
+        "Generation {} | live: {} | delay: {:?} | {}",
+        generation,
+        space.count_live(style),
+        delay,
+        state
+    );
+    let help = "space: pause/resume  n: step  +/-: speed  q: quit";
+
+    let mut lines = vec![header, help.to_string(), String::new()];
+    lines.extend(layout(&space.panels(style), cols as usize));
+    for (i, line) in lines.iter().take(rows as usize).enumerate() {
+        queue!(stdout, MoveTo(0, i as u16))?;
+        write!(stdout, "{}", line)?;
+    }
+    stdout.flush()?;
+
+    Ok(())
+}
+
+// What to do after waiting for a key or for the next frame to be due
+enum Action {
+    Step,
+    Redraw,
+    Quit,
+}
+
+/// An animation in the terminal, drawn a frame at a time by the loop that runs the simulation.
+///
+/// The terminal is restored when the visualizer is dropped.
+pub struct Visualizer<'a, C> {
+    style: &'a Style<C>,
+    delay: Duration,
+    paused: bool,
+    _guard: TerminalGuard,
+}
+
+impl<'a, C> Visualizer<'a, C> {
+    pub fn new(style: &'a Style<C>) -> Result<Visualizer<'a, C>> {
+        Ok(Visualizer {
+            style,
+            delay: Duration::from_millis(250),
+            paused: false,
+            _guard: TerminalGuard::new()?,
+        })
+    }
+
+    /// Draw `space` at `generation`, and wait until the next generation is due. Returns false if
+    /// the user quit, after which the simulation can carry on without being drawn.
+    pub fn frame<S: Render<Cell = C>>(&mut self, space: &S, generation: usize) -> Result<bool> {
+        loop {
+            let state = if self.paused { "paused" } else { "running" };
+            draw(space, self.style, generation, self.delay, state)?;
+            match self.wait(false)? {
+                Action::Step => return Ok(true),
+                Action::Redraw => (),
+                Action::Quit => return Ok(false),
+            }
+        }
+    }
+
+    /// Draw the final state of the simulation, which stopped with `status`, until the user quits.
+    pub fn finish<S: Render<Cell = C>>(
+        mut self,
+        space: &S,
+        generation: usize,
+        status: Status,
+    ) -> Result<()> {
+        let state = match status {
+            Status::Fixed => "fixed point reached".to_string(),
+            Status::Cycle { start, period } => {
+                format!("cycle of period {} from generation {}", period, start)
+            }
+            Status::Changed => "generation limit reached".to_string(),
+        };
+        loop {
+            draw(space, self.style, generation, self.delay, &state)?;
+            if let Action::Quit = self.wait(true)? {
+                return Ok(());
+            }
+        }
+    }
+
+    // Wait for a key, or for the next frame to be due unless paused or `done`
+    fn wait(&mut self, done: bool) -> Result<Action> {
+        let deadline = Instant::now() + self.delay;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if self.paused || done {
+                event::poll(Duration::from_secs(3600))?;
+            } else if !event::poll(timeout)? {
+                return Ok(Action::Step);
+            }
+
+            match event::read()? {
+                Event::Key(KeyEvent { code, modifiers }) => match code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(Action::Quit),
+                    KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(Action::Quit)
+                    }
+                    KeyCode::Char(' ') => self.paused = !self.paused,
+                    KeyCode::Char('n') | KeyCode::Right if self.paused && !done => {
+                        return Ok(Action::Step)
+                    }
+                    KeyCode::Char('+') | KeyCode::Char('=') => {
+                        self.delay = (self.delay / 2).max(MIN_DELAY)
+                    }
+                    KeyCode::Char('-') => self.delay = (self.delay * 2).min(MAX_DELAY),
+                    _ => continue,
+                },
+                // Redraw to fit the new size
+                Event::Resize(_, _) => (),
+                Event::Mouse(_) => continue,
+            }
+            return Ok(Action::Redraw);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() -> Result<()> {
+        let mut lattice = Lattice::new(3);
+        lattice.insert(vec![0, 0, 0], true);
+        lattice.insert(vec![1, 0, 1], true);
+        let style = Style {
+            glyph: |c: &bool| {
+                if *c {
+                    ('#', Color::Green)
+                } else {
+                    ('.', Color::Reset)
+                }
+            },
+            live: |c: &bool| *c,
+        };
+
+        let panels = lattice.panels(&style);
+        assert_eq!(2, panels.len());
+        assert_eq!("z=0", panels[0].title);
+        assert_eq!("z=1", panels[1].title);
+        assert_eq!(2, lattice.count_live(&style));
+
+        // Both panels fit side by side in a wide terminal, but not in a narrow one
+        assert_eq!(3, layout(&panels, 80).len());
+        assert_eq!(6, layout(&panels, 4).len());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+//! Cross-check alternative solutions to the same puzzle part against each other.
+//!
+//! Each day registers its strategies for a part (e.g. a brute-force solution next to a clever
+//! one), and `run` checks all of them on the real input, the examples and randomly generated
+//! inputs, reporting any case where they disagree.
+use std::fmt::Debug;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use structopt::StructOpt;
+
+/// The subcommand a day accepts alongside solving the puzzle.
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Check that every part 2 strategy gives the same answer
+    Verify(VerifyOpt),
+}
+
+/// Options for a day's `verify` subcommand.
+#[derive(Debug, StructOpt)]
+pub struct VerifyOpt {
+    /// Puzzle inputs to check, in addition to the generated ones
+    #[structopt(parse(from_os_str))]
+    pub files: Vec<PathBuf>,
+
+    /// Number of random inputs to generate
+    #[structopt(long, default_value = "100")]
+    pub cases: usize,
+
+    /// Seed for the random inputs
+    #[structopt(long, default_value = "2020")]
+    pub seed: u64,
+}
+
+/// One way of solving a puzzle part.
+pub struct Strategy<I, O> {
+    pub name: &'static str,
+    pub run: fn(&I) -> Result<O>,
+    /// Whether the strategy is fast enough to run on this input. Always true if None.
+    pub feasible: Option<fn(&I) -> bool>,
+}
+
+impl<I, O> Strategy<I, O> {
+    pub fn new(name: &'static str, run: fn(&I) -> Result<O>) -> Strategy<I, O> {
+        Strategy {
+            name,
+            run,
+            feasible: None,
+        }
+    }
+
+    /// Only run this strategy on inputs for which `feasible` returns true.
+    pub fn only_if(mut self, feasible: fn(&I) -> bool) -> Strategy<I, O> {
+        self.feasible = Some(feasible);
+        self
+    }
+}
+
+/// A labelled input to check.
+pub struct Case<I> {
+    pub label: String,
+    pub input: I,
+}
+
+impl<I> Case<I> {
+    pub fn new(label: &str, input: I) -> Case<I> {
+        Case {
+            label: label.to_string(),
+            input,
+        }
+    }
+}
+
+/// Read and parse every file given on the command line.
+pub fn file_cases<I>(opt: &VerifyOpt, parse: fn(&str) -> Result<I>) -> Result<Vec<Case<I>>> {
+    opt.files
+        .iter()
+        .map(|path| {
+            let input = parse(&fs::read_to_string(path)?)?;
+            Ok(Case {
+                label: path.display().to_string(),
+                input,
+            })
+        })
+        .collect()
+}
+
+/// `count` cases built by `generate`, each with its own random number generator.
+pub fn generated_cases<I>(opt: &VerifyOpt, generate: fn(&mut Rng) -> I) -> Vec<Case<I>> {
+    let mut rng = Rng::new(opt.seed);
+    (0..opt.cases)
+        .map(|i| Case {
+            label: format!("generated #{}", i),
+            input: generate(&mut rng),
+        })
+        .collect()
+}
+
+/// Run every strategy on every case and print a line per case. Returns the number of cases on
+/// which the strategies disagreed.
+///
+/// Strategies that return an error agree with each other regardless of the message, so "no
+/// solution" counts as an answer.
+pub fn verify<I, O>(part: &str, strategies: &[Strategy<I, O>], cases: &[Case<I>]) -> usize
+where
+    O: PartialEq + Debug,
+{
+    let mut mismatches = 0;
+
+    for case in cases {
+        let results: Vec<(&str, Result<O>)> = strategies
+            .iter()
+            .filter(|s| s.feasible.is_none_or(|f| f(&case.input)))
+            .map(|s| (s.name, (s.run)(&case.input)))
+            .collect();
+        let skipped = strategies.len() - results.len();
+
+        let agree = results.windows(2).all(|w| match (&w[0].1, &w[1].1) {
+            (Ok(a), Ok(b)) => a == b,
+            (Err(_), Err(_)) => true,
+            _ => false,
+        });
+
+        if agree {
+            let answer = match results.first() {
+                Some((_, Ok(v))) => format!("{:?}", v),
+                Some((_, Err(e))) => format!("error ({})", e),
+                None => "nothing".to_string(),
+            };
+            print!(
+                "{} on {}: ok, {} strategies agree on {}",
+                part,
+                case.label,
+                results.len(),
+                answer
+            );
+            if skipped > 0 {
+                print!(" ({} skipped)", skipped);
+            }
+            println!();
+        } else {
+            mismatches += 1;
+            println!("{} on {}: MISMATCH", part, case.label);
+            for (name, result) in &results {
+                match result {
+                    Ok(v) => println!("    {}: {:?}", name, v),
+                    Err(e) => println!("    {}: error ({})", name, e),
+                }
+            }
+        }
+    }
+
+    mismatches
+}
+
+/// Run `verify` and fail if the strategies disagreed on any case.
+pub fn run<I, O>(part: &str, strategies: &[Strategy<I, O>], cases: &[Case<I>]) -> Result<()>
+where
+    O: PartialEq + Debug,
+{
+    match verify(part, strategies, cases) {
+        0 => Ok(()),
+        n => Err(anyhow!("{} mismatches", n)),
+    }
+}
+
+/// A small xorshift64* pseudo-random number generator, so generated inputs are reproducible from
+/// a seed.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // The state must never be zero
+        Rng(seed ^ 0x9e37_79b9_7f4a_7c15 | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `low..high`.
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        assert!(low < high, "empty range {}..{}", low, high);
+        low + (self.next_u64() % (high - low) as u64) as i64
+    }
+
+    /// A uniformly chosen index below `len`.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0, len as i64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.index(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let strategies: Vec<Strategy<u32, u32>> = vec![
+            Strategy::new("double", |n| Ok(n * 2)),
+            Strategy::new("add", |n| Ok(n + n)),
+            Strategy::new("shift", |n| Ok(n << 1)).only_if(|n| *n < 10),
+        ];
+        let cases = vec![Case::new("small", 3), Case::new("large", 300)];
+        assert_eq!(0, verify("part 1", &strategies, &cases));
+
+        let strategies: Vec<Strategy<u32, u32>> = vec![
+            Strategy::new("square", |n| Ok(n * n)),
+            Strategy::new("double", |n| Ok(n * 2)),
+            Strategy::new("fails", |_| Err(anyhow!("no solution"))),
+        ];
+        let cases = vec![Case::new("two", 2), Case::new("three", 3)];
+        assert_eq!(2, verify("part 1", &strategies, &cases));
+        assert!(run("part 1", &strategies, &cases).is_err());
+    }
+
+    #[test]
+    fn test_rng() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..100 {
+            let n = a.range(-5, 5);
+            assert_eq!(n, b.range(-5, 5));
+            assert!((-5..5).contains(&n));
+        }
+    }
+}
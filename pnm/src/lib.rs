@@ -0,0 +1,321 @@
+//! Binary PPM (color) and PGM (grayscale) export of grid states, without an image library.
+//!
+//! An [`Image`] stores a palette index per pixel. The [`Palette`] and scale factor are only
+//! applied when the image is written, so the same frames can be exported in different styles.
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use structopt::StructOpt;
+
+/// An ordered list of RGB colors, indexed by the values stored in an [`Image`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette(pub Vec<[u8; 3]>);
+
+impl Palette {
+    /// The color for `index`, or magenta if the palette is too short, so that mistakes stand out.
+    pub fn color(&self, index: usize) -> [u8; 3] {
+        self.0.get(index).copied().unwrap_or([255, 0, 255])
+    }
+
+    /// The luma of the color for `index`, as used in grayscale images.
+    pub fn gray(&self, index: usize) -> u8 {
+        let [r, g, b] = self.color(index);
+        ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8
+    }
+}
+
+/// Parse a comma-separated list of hex colors, e.g. "ffffff,#000000,2e8b57".
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Palette, String> {
+        s.split(',')
+            .map(|hex| {
+                let hex = hex.trim().trim_start_matches('#');
+                if hex.len() != 6 || !hex.is_ascii() {
+                    return Err(format!("Invalid color: \"{}\"", hex));
+                }
+                let channel = |i: usize| {
+                    u8::from_str_radix(&hex[i..i + 2], 16)
+                        .map_err(|_| format!("Invalid color: \"{}\"", hex))
+                };
+                Ok([channel(0)?, channel(2)?, channel(4)?])
+            })
+            .collect::<Result<Vec<[u8; 3]>, String>>()
+            .map(Palette)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Binary color image (P6)
+    Ppm,
+    /// Binary grayscale image (P5)
+    Pgm,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Pgm => "pgm",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "ppm" => Ok(Format::Ppm),
+            "pgm" => Ok(Format::Pgm),
+            _ => Err(format!("Unknown image format: \"{}\"", s)),
+        }
+    }
+}
+
+/// A grid of palette indices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<usize>,
+}
+
+impl Image {
+    /// An image filled with palette index 0.
+    pub fn new(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    /// An image whose pixel at (x, y) is `f(x, y)`.
+    pub fn from_fn<F>(width: usize, height: usize, f: F) -> Image
+    where
+        F: Fn(usize, usize) -> usize,
+    {
+        Image {
+            width,
+            height,
+            pixels: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| f(x, y))
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> usize {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, index: usize) {
+        self.pixels[y * self.width + x] = index;
+    }
+
+    /// Copy `other` into this image with its top left corner at (x, y), clipping anything that
+    /// falls outside.
+    pub fn blit(&mut self, other: &Image, x: usize, y: usize) {
+        for oy in 0..other.height.min(self.height.saturating_sub(y)) {
+            for ox in 0..other.width.min(self.width.saturating_sub(x)) {
+                self.set(x + ox, y + oy, other.get(ox, oy));
+            }
+        }
+    }
+
+    /// Write the image, with every pixel drawn as a `scale` x `scale` square.
+    pub fn write<W: Write>(
+        &self,
+        out: &mut W,
+        format: Format,
+        palette: &Palette,
+        scale: usize,
+    ) -> io::Result<()> {
+        let scale = scale.max(1);
+        let magic = match format {
+            Format::Ppm => "P6",
+            Format::Pgm => "P5",
+        };
+        write!(
+            out,
+            "{}\n{} {}\n255\n",
+            magic,
+            self.width * scale,
+            self.height * scale
+        )?;
+
+        let mut row = vec![];
+        for y in 0..self.height {
+            row.clear();
+            for x in 0..self.width {
+                let index = self.get(x, y);
+                for _ in 0..scale {
+                    match format {
+                        Format::Ppm => row.extend_from_slice(&palette.color(index)),
+                        Format::Pgm => row.push(palette.gray(index)),
+                    }
+                }
+            }
+            for _ in 0..scale {
+                out.write_all(&row)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The command-line options for exporting frames, to be flattened into a day's own options.
+#[derive(Debug, StructOpt)]
+pub struct FrameOpt {
+    /// Write an image of every frame into this directory
+    #[structopt(long, parse(from_os_str))]
+    pub frames: Option<PathBuf>,
+
+    /// Image format of the frames (ppm or pgm)
+    #[structopt(long, default_value = "ppm")]
+    pub format: Format,
+
+    /// Width in pixels of each cell in the frames [default: depends on the day]
+    #[structopt(long)]
+    pub scale: Option<usize>,
+
+    /// Comma-separated hex colors of the frames, in the order the README lists for each day
+    #[structopt(long)]
+    pub palette: Option<Palette>,
+}
+
+impl FrameOpt {
+    /// A writer of frames named after `prefix`, if a directory was given, using the day's own
+    /// `palette` and `scale` unless others were given.
+    pub fn writer(
+        &self,
+        prefix: &str,
+        palette: &str,
+        scale: usize,
+    ) -> io::Result<Option<FrameWriter>> {
+        let dir = match &self.frames {
+            Some(dir) => dir,
+            None => return Ok(None),
+        };
+        let palette = match &self.palette {
+            Some(palette) => palette.clone(),
+            None => palette.parse().expect("the default palette is valid"),
+        };
+
+        FrameWriter::new(
+            dir,
+            prefix,
+            self.format,
+            palette,
+            self.scale.unwrap_or(scale),
+        )
+        .map(Some)
+    }
+}
+
+/// Writes a numbered sequence of images (`<prefix>-0000.ppm`, `<prefix>-0001.ppm`, ...) into a
+/// directory.
+#[derive(Debug)]
+pub struct FrameWriter {
+    dir: PathBuf,
+    prefix: String,
+    format: Format,
+    palette: Palette,
+    scale: usize,
+    count: usize,
+}
+
+impl FrameWriter {
+    /// Create the output directory if it doesn't exist yet.
+    pub fn new(
+        dir: &Path,
+        prefix: &str,
+        format: Format,
+        palette: Palette,
+        scale: usize,
+    ) -> io::Result<FrameWriter> {
+        fs::create_dir_all(dir)?;
+
+        Ok(FrameWriter {
+            dir: dir.to_path_buf(),
+            prefix: prefix.to_string(),
+            format,
+            palette,
+            scale,
+            count: 0,
+        })
+    }
+
+    /// Write the next frame, returning the path it was written to.
+    pub fn write(&mut self, image: &Image) -> io::Result<PathBuf> {
+        let path = self.dir.join(format!(
+            "{}-{:04}.{}",
+            self.prefix,
+            self.count,
+            self.format.extension()
+        ));
+        let mut out = BufWriter::new(File::create(&path)?);
+        image.write(&mut out, self.format, &self.palette, self.scale)?;
+        out.flush()?;
+
+        self.count += 1;
+        Ok(path)
+    }
+
+    /// The number of frames written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette() -> Result<(), String> {
+        let palette: Palette = "ffffff, #000000,ff8000".parse()?;
+
+        assert_eq!([255, 128, 0], palette.color(2));
+        assert_eq!([255, 0, 255], palette.color(3));
+        assert_eq!(255, palette.gray(0));
+        assert_eq!(0, palette.gray(1));
+        assert!("fff".parse::<Palette>().is_err());
+        assert!("gggggg".parse::<Palette>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write() -> io::Result<()> {
+        let image = Image::from_fn(2, 1, |x, _| x);
+        let palette = Palette(vec![[0, 0, 0], [255, 255, 255]]);
+
+        let mut ppm = vec![];
+        image.write(&mut ppm, Format::Ppm, &palette, 1)?;
+        assert_eq!(b"P6\n2 1\n255\n\x00\x00\x00\xff\xff\xff".to_vec(), ppm);
+
+        let mut pgm = vec![];
+        image.write(&mut pgm, Format::Pgm, &palette, 2)?;
+        assert_eq!(
+            b"P5\n4 2\n255\n\x00\x00\xff\xff\x00\x00\xff\xff".to_vec(),
+            pgm
+        );
+
+        Ok(())
+    }
+}